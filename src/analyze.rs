@@ -1,5 +1,19 @@
-use crate::{builder::builder::Builder, cmake, config::Config};
+use crate::{
+    builder::{
+        builder::Builder,
+        coverage::Coverage,
+        diff::{ApiDiff, Changelog},
+        export::ApiExport,
+        links::check_links,
+        lint::lint_docs,
+        reference::Reference,
+    },
+    cmake,
+    config::Config,
+};
 use indicatif::{ProgressBar, ProgressStyle};
+use log::{error, info, warn};
+use serde::Deserialize;
 use std::{fs, path::PathBuf, process::Command, sync::Arc, time::Duration};
 
 fn run_command(cmd: &String) -> Result<(), String> {
@@ -34,7 +48,46 @@ fn create_analyzable_file(config: Arc<Config>) -> Result<PathBuf, String> {
     Ok(out_path)
 }
 
-async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(), String> {
+/// What to generate from the analyzed headers
+#[derive(Clone, Copy, Default, PartialEq, Debug, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// The HTML docs site
+    #[default]
+    Html,
+    /// A single api.json file describing the whole documented API
+    Json,
+    /// Markdown pages, for hosting the docs somewhere that renders markdown
+    Markdown,
+    /// A plain text quick reference for every class and function
+    Text,
+    /// Man pages for every class and function
+    Man,
+}
+
+/// Options for how to build the docs
+#[derive(Clone, Default)]
+pub struct BuildOptions {
+    /// What to generate
+    pub format: OutputFormat,
+    /// Skip invoking CMake and rely on an existing build dir
+    pub skip_build: bool,
+    /// Fail if less than this percentage of the public API is documented
+    pub coverage_fail_under: Option<f64>,
+    /// Treat problems in doc comments as errors
+    pub strict: bool,
+    /// Only check the doc comments and the links between pages. The pages
+    /// are still written, so the output dir should be a temporary one
+    pub check_only: bool,
+    /// API export of a previous version to generate a changelog against
+    pub changes_since: Option<PathBuf>,
+}
+
+async fn analyze_with_clang(
+    config: Arc<Config>,
+    args: &[String],
+    options: &BuildOptions,
+) -> Result<(), String> {
     // Initialize clang
     let clang = clang::Clang::new()?;
     let index = clang::Index::new(&clang, false, true);
@@ -64,15 +117,102 @@ async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(),
     pbar.enable_steady_tick(Duration::from_millis(50));
 
     // Create parser
-    let unit = index.parser(&target_src).arguments(args).parse()?;
+    let unit = index
+        .parser(&target_src)
+        .arguments(args)
+        .detailed_preprocessing_record(true)
+        .parse()?;
 
     // Build the navbar first
     pbar.set_message("Setting up");
-    let builder = Builder::new(config, unit.get_entity(), &clang, &index, args)?;
+    let builder = Builder::new(
+        config,
+        unit.get_entity(),
+        &clang,
+        &index,
+        args,
+        options.format,
+    )?;
+
+    // Build the doc files. Checking only builds the pages, which go to a
+    // temporary directory, so the links in them can be checked
+    if options.check_only {
+        pbar.set_message("Building docs");
+        builder.build(Some(pbar.clone())).await?;
+    } else {
+        match options.format {
+            OutputFormat::Html | OutputFormat::Markdown => {
+                pbar.set_message("Building docs");
+                builder.build(Some(pbar.clone())).await?;
+
+                if let Some(ref path) = options.changes_since {
+                    pbar.set_message("Building changelog");
+                    let diff = ApiDiff::new(&ApiExport::load(path)?, &ApiExport::new(&builder));
+                    // The markdown output already has the changelog as a page
+                    if options.format == OutputFormat::Html {
+                        fs::write(
+                            builder.config.output_dir.join("changelog.md"),
+                            diff.to_markdown(),
+                        )
+                        .map_err(|e| format!("Unable to save changelog {e}"))?;
+                    }
+                    for handle in builder.create_output_for(&Changelog::new(diff))? {
+                        handle.await.map_err(|e| format!("Unable to join {e}"))??;
+                    }
+                }
+
+                // Only once every page is written, so pages built after the
+                // main ones like the changelog aren't treated as stale
+                if options.format == OutputFormat::Html {
+                    builder.cache.remove_stale_pages();
+                    builder.cache.save();
+                }
+            }
+            OutputFormat::Json => {
+                pbar.set_message("Exporting API");
+                fs::write(
+                    builder.config.output_dir.join("api.json"),
+                    serde_json::to_string_pretty(&ApiExport::new(&builder))
+                        .map_err(|e| format!("Unable to save API export {e}"))?,
+                )
+                .map_err(|e| format!("Unable to save API export {e}"))?;
+            }
+            OutputFormat::Text => {
+                pbar.set_message("Writing reference");
+                Reference::new(&builder).write_text(&builder.config.output_dir)?;
+            }
+            OutputFormat::Man => {
+                pbar.set_message("Writing man pages");
+                Reference::new(&builder).write_man(&builder.config.output_dir)?;
+            }
+        }
+    }
+
+    // Check that doc comments match what they document
+    pbar.set_message("Linting docs");
+    let mut problems = lint_docs(&builder);
 
-    // Build the doc files
-    pbar.set_message("Building docs");
-    builder.build(Some(pbar.clone())).await?;
+    // Check that the links in the generated pages lead somewhere
+    if options.format == OutputFormat::Html {
+        pbar.set_message("Checking links");
+        problems.extend(check_links(&builder));
+    }
+
+    // Check how much of the API is documented
+    pbar.set_message("Checking coverage");
+    let coverage = Coverage::new(&builder);
+    if !options.check_only {
+        fs::write(
+            builder.config.output_dir.join("coverage.json"),
+            serde_json::to_string(&coverage).map_err(|e| format!("Unable to save coverage {e}"))?,
+        )
+        .map_err(|e| format!("Unable to save coverage {e}"))?;
+        fs::write(
+            builder.config.output_dir.join("coverage.txt"),
+            coverage.summary() + "\n",
+        )
+        .map_err(|e| format!("Unable to save coverage {e}"))?;
+    }
 
     pbar.set_message("Cleaning up files");
 
@@ -81,12 +221,42 @@ async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(),
 
     pbar.finish_using_style();
 
+    if builder.cache.reused() > 0 {
+        info!(
+            "Reused {} unchanged pages from the previous build",
+            builder.cache.reused()
+        );
+    }
+
+    // Checking is always strict, since there's no output to look at anyway
+    let strict = options.strict || options.check_only;
+    for problem in &problems {
+        if strict {
+            error!("{problem}");
+        } else {
+            warn!("{problem}");
+        }
+    }
+    if strict && !problems.is_empty() {
+        return Err(format!("Found {} problems in the docs", problems.len()));
+    }
+
+    info!("Documentation coverage:\n{}", coverage.summary());
+    if let Some(min) = options.coverage_fail_under
+        && coverage.percentage() < min
+    {
+        return Err(format!(
+            "Documentation coverage {:.1}% is below the required {min}%",
+            coverage.percentage()
+        ));
+    }
+
     Ok(())
 }
 
-async fn analyze_with_cmake(config: Arc<Config>, skip_build: bool) -> Result<(), String> {
+async fn analyze_with_cmake(config: Arc<Config>, options: &BuildOptions) -> Result<(), String> {
     // Configure the cmake project
-    if !skip_build {
+    if !options.skip_build {
         cmake::cmake_configure(
             &config.cmake.as_ref().unwrap().build_dir,
             &config.cmake.as_ref().unwrap().config_args,
@@ -94,7 +264,7 @@ async fn analyze_with_cmake(config: Arc<Config>, skip_build: bool) -> Result<(),
     }
 
     // Build the cmake project
-    if !skip_build && config.cmake.as_ref().unwrap().build {
+    if !options.skip_build && config.cmake.as_ref().unwrap().build {
         cmake::cmake_build(
             &config.cmake.as_ref().unwrap().build_dir,
             &config.cmake.as_ref().unwrap().build_args,
@@ -104,13 +274,14 @@ async fn analyze_with_cmake(config: Arc<Config>, skip_build: bool) -> Result<(),
     analyze_with_clang(
         config.clone(),
         &cmake::cmake_compile_args_for(config).expect("Unable to infer CMake compile args"),
+        options,
     )
     .await?;
 
     Ok(())
 }
 
-pub async fn create_docs(config: Arc<Config>, skip_build: bool) -> Result<(), String> {
+pub async fn create_docs(config: Arc<Config>, options: BuildOptions) -> Result<(), String> {
     // Execute prebuild commands
     if let Some(cmds) = config.run.as_ref().map(|c| &c.prebuild) {
         for cmd in cmds {
@@ -120,10 +291,10 @@ pub async fn create_docs(config: Arc<Config>, skip_build: bool) -> Result<(), St
 
     // Build based on mode
     if config.cmake.is_some() {
-        analyze_with_cmake(config, skip_build).await
+        analyze_with_cmake(config, &options).await
     }
     // Build with extra compile args only
     else {
-        analyze_with_clang(config.clone(), &config.analysis.compile_args).await
+        analyze_with_clang(config.clone(), &config.analysis.compile_args, &options).await
     }
 }
@@ -2,31 +2,42 @@ use clang::{Clang, Entity};
 use indicatif::ProgressBar;
 use serde_json::json;
 use std::{collections::HashMap, sync::Arc};
-use strfmt::strfmt;
 use tokio::task::JoinHandle;
 
 use crate::{
+    analyze::OutputFormat,
     config::Config,
     html::{
         process::{minify_css, minify_html, minify_js},
         GenHtml, Html,
+        template::Template,
     },
     url::UrlPath,
 };
 
 use super::{
+    cache::{hash_of, BuildCache},
     files::Root,
-    namespace::Namespace,
-    traits::{BuildResult, Entry, OutputEntry},
+    hierarchy::{Hierarchy, HierarchyPage},
+    includes::{IncludeGraph, IncludeReport},
+    markdown_output::markdown_path,
+    namespace::{CppItem, Namespace},
+    search::SearchIndex,
+    shared::member_fun_link,
+    traits::{ASTEntry, BuildResult, Entry, OutputEntry},
     tutorial::TutorialFolder,
 };
 
 pub struct Builder<'e> {
     pub config: Arc<Config>,
     pub root: Namespace<'e>,
+    pub hierarchy: Hierarchy<'e>,
+    pub includes: IncludeGraph,
     pub _clang: &'e Clang,
     pub index: &'e clang::Index<'e>,
     pub args: &'e [String],
+    pub cache: BuildCache,
+    pub format: OutputFormat,
     file_roots: Vec<Root>,
     tutorials: TutorialFolder,
     nav_cache: Option<String>,
@@ -39,21 +50,83 @@ impl<'e> Builder<'e> {
         clang: &'e Clang,
         index: &'e clang::Index<'e>,
         args: &'e [String],
+        format: OutputFormat,
     ) -> Result<Self, String> {
+        let file_roots = Root::from_config(config.clone());
+        let includes = IncludeGraph::new(&root, &file_roots, &config);
+        let root = Namespace::new_root(root, config.clone());
+        let hierarchy = Hierarchy::new(&root);
+        let tutorials = TutorialFolder::from_config(config.clone());
+
+        // Adding, removing or renaming anything can affect links on any page,
+        // so the navigation and everything links can point to are part of
+        // every page's inputs
+        let mut link_targets = Vec::new();
+        get_link_targets(&root, &mut link_targets);
+        let nav = hash_of(&(
+            root.nav().to_json(config.clone()).to_string(),
+            tutorials.nav().to_json(config.clone()).to_string(),
+            link_targets,
+        ));
+
         Self {
             config: config.clone(),
-            root: Namespace::new_root(root, config.clone()),
+            root,
+            hierarchy,
+            includes,
             _clang: clang,
             index,
             args,
-            file_roots: Root::from_config(config.clone()),
-            tutorials: TutorialFolder::from_config(config),
+            cache: BuildCache::load(config.clone(), args, nav),
+            format,
+            file_roots,
+            tutorials,
             nav_cache: None,
         }
         .setup()
     }
 
     fn setup(mut self) -> Result<Self, String> {
+        // The markdown output has no use for styles, scripts or icons
+        if self.format == OutputFormat::Html {
+            self.copy_site_assets()?;
+        }
+
+        // copy tutorial assets
+        if let Some(ref tutorials) = self.config.tutorials {
+            for asset in &tutorials.assets {
+                let output = self.config.output_dir.join(
+                    // if the tutorials are in docs and the assets are in
+                    // docs/assets, then they are probably referenced with
+                    // just assets/image.png so we should strip the docs
+                    // part
+                    asset.strip_prefix(&tutorials.dir).unwrap_or(asset),
+                );
+                if let Some(parent) = output.parent() {
+                    std::fs::create_dir_all(self.config.output_dir.join(parent)).map_err(|e| {
+                        format!(
+                            "Unable to create asset directory '{}': {e}",
+                            output.to_string_lossy()
+                        )
+                    })?;
+                }
+                std::fs::copy(self.config.input_dir.join(asset), output).map_err(|e| {
+                    format!(
+                        "Unable to copy asset '{}': {e}, {}",
+                        asset.to_string_lossy(),
+                        self.config.input_dir.join(asset).to_string_lossy(),
+                    )
+                })?;
+            }
+        }
+
+        // prebuild nav for performance
+        self.prebuild()?;
+
+        Ok(self)
+    }
+
+    fn copy_site_assets(&self) -> Result<(), String> {
         // copy & minify CSS
         for script in &self.config.scripts.css {
             std::fs::write(
@@ -91,41 +164,27 @@ impl<'e> Builder<'e> {
             icon_dir.write(ico_file).unwrap();
         }
 
-        // copy tutorial assets
-        if let Some(ref tutorials) = self.config.tutorials {
-            for asset in &tutorials.assets {
-                let output = self.config.output_dir.join(
-                    // if the tutorials are in docs and the assets are in
-                    // docs/assets, then they are probably referenced with
-                    // just assets/image.png so we should strip the docs
-                    // part
-                    asset.strip_prefix(&tutorials.dir).unwrap_or(asset),
-                );
-                if let Some(parent) = output.parent() {
-                    std::fs::create_dir_all(self.config.output_dir.join(parent)).map_err(|e| {
-                        format!(
-                            "Unable to create asset directory '{}': {e}",
-                            output.to_string_lossy()
-                        )
-                    })?;
-                }
-                std::fs::copy(self.config.input_dir.join(asset), output).map_err(|e| {
-                    format!(
-                        "Unable to copy asset '{}': {e}, {}",
-                        asset.to_string_lossy(),
-                        self.config.input_dir.join(asset).to_string_lossy(),
-                    )
-                })?;
-            }
-        }
-
-        // prebuild nav for performance
-        self.prebuild()?;
-
-        Ok(self)
+        Ok(())
     }
 
     pub fn create_output_for<E: OutputEntry<'e>>(&'e self, entry: &E) -> BuildResult {
+        // Markdown pages are cheap to write so they skip the cache
+        if self.format == OutputFormat::Markdown {
+            return Ok(vec![Self::create_markdown_in_thread(
+                self.config.clone(),
+                entry.url(),
+                entry.markdown(self),
+            )]);
+        }
+
+        // Skip pages whose inputs haven't changed since the last build
+        self.cache.add_output(&entry.url());
+        if let Some(key) = entry.cache_key(self)
+            && self.cache.is_fresh(&entry.url(), key)
+        {
+            return Ok(Vec::new());
+        }
+
         let (template, vars) = entry.output(self);
         Ok(vec![Self::create_output_in_thread(
             self.config.clone(),
@@ -135,17 +194,20 @@ impl<'e> Builder<'e> {
             entry.url(),
             template,
             vars,
+            entry.context(self),
         )])
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn create_output_in_thread(
         config: Arc<Config>,
         nav: String,
         name: String,
         description: String,
         target_url: UrlPath,
-        template: Arc<String>,
+        template: Arc<Template>,
         vars: Vec<(&'static str, Html)>,
+        context: Option<serde_json::Value>,
     ) -> JoinHandle<Result<UrlPath, String>> {
         tokio::spawn(async move {
             let title = if name.is_empty() {
@@ -170,7 +232,8 @@ impl<'e> Builder<'e> {
             );
 
             let content = minify_html(
-                strfmt(&template, &fmt)
+                template
+                    .render(&fmt, context.as_ref())
                     .map_err(|e| format!("Unable to format {target_url}: {e}"))?,
             )?;
 
@@ -178,14 +241,20 @@ impl<'e> Builder<'e> {
             page.extend(HashMap::from([
                 (
                     "head_content".to_owned(),
-                    strfmt(&config.templates.head, &fmt)
+                    config
+                        .templates
+                        .head
+                        .render(&fmt, context.as_ref())
                         .map_err(|e| format!("Unable to format head for {target_url}: {e}"))?,
                 ),
                 ("navbar_content".to_owned(), nav),
                 ("main_content".to_owned(), content.clone()),
             ]));
             let page = minify_html(
-                strfmt(&config.templates.page, &page)
+                config
+                    .templates
+                    .page
+                    .render(&page, context.as_ref())
                     .map_err(|e| format!("Unable to format {target_url}: {e}"))?,
             )?;
 
@@ -233,13 +302,36 @@ impl<'e> Builder<'e> {
         })
     }
 
+    fn create_markdown_in_thread(
+        config: Arc<Config>,
+        target_url: UrlPath,
+        content: String,
+    ) -> JoinHandle<Result<UrlPath, String>> {
+        tokio::spawn(async move {
+            let path = config.output_dir.join(markdown_path(&target_url));
+
+            // Make sure output directory exists
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent)
+                    .await
+                    .map_err(|e| format!("Unable to create directory for {target_url}: {e}"))?;
+            }
+
+            tokio::fs::write(path, content)
+                .await
+                .map_err(|e| format!("Unable to save {target_url}: {e}"))?;
+
+            Ok(target_url)
+        })
+    }
+
     fn all_entries(&self) -> Vec<&dyn Entry<'e>> {
         self.root
             .entries
             .iter()
             .map(|p| p.1 as &dyn Entry<'e>)
             .chain(self.file_roots.iter().map(|p| p as &dyn Entry<'e>))
-            .chain([&self.tutorials as &dyn Entry])
+            .chain([&self.tutorials as &dyn Entry, &HierarchyPage, &IncludeReport])
             .collect()
     }
 
@@ -250,7 +342,7 @@ impl<'e> Builder<'e> {
         Ok(())
     }
 
-    pub async fn build(&self, pbar: Option<Arc<ProgressBar>>) -> Result<(), String> {
+    pub async fn build(&'e self, pbar: Option<Arc<ProgressBar>>) -> Result<(), String> {
         let mut handles = Vec::new();
 
         // Spawn threads for creating docs for all entries
@@ -277,31 +369,34 @@ impl<'e> Builder<'e> {
         .collect::<Result<Result<Vec<_>, _>, _>>()
         .map_err(|e| format!("Unable to join {e}"))??;
 
+        // The metadata is only used by the HTML site
+        if self.format == OutputFormat::Markdown {
+            return Ok(());
+        }
+
         if let Some(pbar) = pbar.clone() {
             pbar.set_message("Generating metadata".to_string());
         }
 
-        tokio::fs::write(
-            self.config.output_dir.join("functions.json"),
-            serde_json::to_string(
-                &self
-                    .root
-                    .nav()
-                    .suboptions_titles(self.config.clone())
-                    .into_iter()
-                    .map(|(n, c)| {
-                        if c > 0 {
-                            format!("{} ({})", n, c + 1)
-                        } else {
-                            n
-                        }
-                    })
-                    .collect::<Vec<_>>(),
+        // The search index is split into shards so large projects don't need
+        // to download everything in one request
+        let search = SearchIndex::new(self, &self.tutorials);
+        let shards = search.shards();
+        for (i, shard) in shards.iter().enumerate() {
+            tokio::fs::write(
+                self.config.output_dir.join(format!("search-{i}.json")),
+                serde_json::to_string(shard)
+                    .map_err(|e| format!("Unable to save search index {e}"))?,
             )
-            .map_err(|e| format!("Unable to save metadata {e}"))?,
+            .await
+            .map_err(|e| format!("Unable to save search index {e}"))?;
+        }
+        tokio::fs::write(
+            self.config.output_dir.join("search.json"),
+            json!({ "shards": shards.len() }).to_string(),
         )
         .await
-        .map_err(|e| format!("Unable to save metadata {e}"))?;
+        .map_err(|e| format!("Unable to save search index {e}"))?;
 
         tokio::fs::write(
             self.config.output_dir.join("nav.json"),
@@ -327,7 +422,10 @@ impl<'e> Builder<'e> {
             return Ok(cached.to_owned());
         }
         let fmt = default_format(self.config.clone());
-        strfmt(&self.config.templates.nav, &fmt)
+        self.config
+            .templates
+            .nav
+            .render(&fmt, None)
             .map_err(|e| format!("Unable to format navbar: {e}"))
     }
 
@@ -337,6 +435,21 @@ impl<'e> Builder<'e> {
     }
 }
 
+/// Anchors of every documented entity and its members, which links like
+/// `@see`s and the ones to overloads and overridden methods point to
+fn get_link_targets(ns: &Namespace, out: &mut Vec<String>) {
+    for entry in ns.entries.values() {
+        if let CppItem::Namespace(ns) = entry {
+            get_link_targets(ns, out);
+            continue;
+        }
+        for entity in entry.entities() {
+            out.extend(member_fun_link(&entity));
+            out.extend(entity.get_children().iter().filter_map(member_fun_link));
+        }
+    }
+}
+
 fn default_format(config: Arc<Config>) -> HashMap<String, String> {
     HashMap::from([
         ("project_name".into(), config.project.name.clone()),
@@ -365,5 +478,12 @@ fn default_format(config: Arc<Config>) -> HashMap<String, String> {
                 .unwrap_or(&UrlPath::new())
                 .to_string(),
         ),
+        (
+            // Inserted into a script as a JSON value, null if the docs aren't
+            // versioned
+            "versions_url".into(),
+            serde_json::to_string(&config.versions_url.as_ref().map(|url| url.to_string()))
+                .unwrap(),
+        ),
     ])
 }
@@ -0,0 +1,175 @@
+use super::traits::EntityMethods;
+use crate::{config::Config, url::UrlPath};
+use clang::Entity;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+pub const CACHE_FILE: &str = ".flash-cache.json";
+
+pub fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheData {
+    /// Hash of everything that affects every page (config, templates,
+    /// compile args, navigation and the Flash version)
+    global: u64,
+    /// Content hashes of the headers, by path
+    headers: HashMap<String, u64>,
+    /// Hash of the inputs each page was last built from, by page URL
+    pages: HashMap<String, u64>,
+    /// URLs of every page the build wrote, including ones that aren't cached
+    #[serde(default)]
+    outputs: BTreeSet<String>,
+}
+
+/// Cache persisted in the output directory so unchanged pages don't have to
+/// be regenerated on every run
+pub struct BuildCache {
+    config: Arc<Config>,
+    previous: CacheData,
+    /// Pages written by the last build, which are removed if this build
+    /// doesn't write them again
+    previous_outputs: BTreeSet<String>,
+    current: Mutex<CacheData>,
+    reused: AtomicUsize,
+}
+
+impl BuildCache {
+    pub fn load(config: Arc<Config>, args: &[String], nav: u64) -> Self {
+        let mut hasher = DefaultHasher::new();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        fs::read_to_string(config.input_dir.join("flash.toml"))
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        for template in config.all_templates() {
+            template.hash(&mut hasher);
+        }
+        args.hash(&mut hasher);
+        nav.hash(&mut hasher);
+        let global = hasher.finish();
+
+        let saved = fs::read_to_string(config.output_dir.join(CACHE_FILE))
+            .ok()
+            .and_then(|data| serde_json::from_str::<CacheData>(&data).ok());
+        let previous_outputs = saved
+            .as_ref()
+            .map(|data| data.outputs.clone())
+            .unwrap_or_default();
+        // Anything global changing means every page has to be rebuilt
+        let previous = saved
+            .filter(|data| data.global == global)
+            .unwrap_or_default();
+
+        Self {
+            config,
+            previous,
+            previous_outputs,
+            current: Mutex::new(CacheData {
+                global,
+                ..Default::default()
+            }),
+            reused: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the content hash of a file, only reading it once per run
+    pub fn file_hash(&self, path: &Path) -> Option<u64> {
+        let key = path.to_string_lossy().to_string();
+        let mut current = self.current.lock().unwrap();
+        if let Some(hash) = current.headers.get(&key) {
+            return Some(*hash);
+        }
+        let hash = hash_of(&fs::read(path).ok()?);
+        current.headers.insert(key, hash);
+        Some(hash)
+    }
+
+    /// Hash the headers of a set of entities. Returns `None` if any of them
+    /// doesn't come from a file
+    pub fn entities_key(&self, entities: &[Entity]) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        for entity in entities {
+            entity.full_name().hash(&mut hasher);
+            self.file_hash(&entity.definition_file()?)?
+                .hash(&mut hasher);
+        }
+        Some(hasher.finish())
+    }
+
+    /// Check if the page at `url` was already built from the same inputs,
+    /// and remember the inputs for the next run
+    pub fn is_fresh(&self, url: &UrlPath, key: u64) -> bool {
+        let page = url.to_raw_string();
+        let fresh = self.previous.pages.get(&page) == Some(&key)
+            && self
+                .config
+                .output_dir
+                .join(url.to_pathbuf())
+                .join("index.html")
+                .exists();
+        self.current.lock().unwrap().pages.insert(page, key);
+        if fresh {
+            self.reused.fetch_add(1, Ordering::Relaxed);
+        }
+        fresh
+    }
+
+    /// Remember that the page at `url` is part of this build
+    pub fn add_output(&self, url: &UrlPath) {
+        self.current
+            .lock()
+            .unwrap()
+            .outputs
+            .insert(url.to_raw_string());
+    }
+
+    /// Delete the pages the last build wrote that this one didn't, like the
+    /// pages of removed classes
+    pub fn remove_stale_pages(&self) {
+        let current = self.current.lock().unwrap();
+        for page in self.previous_outputs.difference(&current.outputs) {
+            let Ok(url) = UrlPath::parse(page) else {
+                continue;
+            };
+            let dir = self.config.output_dir.join(url.to_pathbuf());
+            for file in ["index.html", "content.html", "metadata.json"] {
+                fs::remove_file(dir.join(file)).ok();
+            }
+            // Pages can be nested in each other, so only directories that
+            // are left empty are removed
+            let mut dir = dir.as_path();
+            while dir != self.config.output_dir && fs::remove_dir(dir).is_ok() {
+                let Some(parent) = dir.parent() else {
+                    break;
+                };
+                dir = parent;
+            }
+        }
+    }
+
+    pub fn reused(&self) -> usize {
+        self.reused.load(Ordering::Relaxed)
+    }
+
+    pub fn save(&self) {
+        let data = serde_json::to_string(&*self.current.lock().unwrap()).unwrap();
+        // Failing to save the cache just means the next build is a full one
+        if let Err(e) = fs::write(self.config.output_dir.join(CACHE_FILE), data) {
+            warn!("Unable to save build cache: {e}");
+        }
+    }
+}
@@ -1,12 +1,17 @@
 use std::sync::Arc;
 
-use crate::{html::Html, url::UrlPath};
+use crate::{
+    html::{Html, template::Template},
+    url::UrlPath,
+};
 use clang::Entity;
 
 use super::{
     builder::Builder,
+    export::page_context,
+    markdown_output::markdown_classlike,
     shared::output_classlike,
-    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry, SubItem},
+    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
 };
 
 pub struct Class<'e> {
@@ -35,12 +40,7 @@ impl<'e> Entry<'e> for Class<'e> {
     }
 
     fn nav(&self) -> NavItem {
-        NavItem::new_link(
-            &self.name(),
-            self.url(),
-            Some(("box", false)),
-            SubItem::for_classlike(&self.entity),
-        )
+        NavItem::new_link(&self.name(), self.url(), Some(("box", false)))
     }
 }
 
@@ -55,14 +55,30 @@ impl<'e> ASTEntry<'e> for Class<'e> {
 }
 
 impl<'e> OutputEntry<'e> for Class<'e> {
-    fn output(&self, builder: &Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+    fn output(&self, builder: &Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
         (
             builder.config.templates.class.clone(),
             output_classlike(self, builder),
         )
     }
 
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        markdown_classlike(self, builder)
+    }
+
+    fn context(&self, builder: &'e Builder<'e>) -> Option<serde_json::Value> {
+        Some(page_context(self, builder))
+    }
+
     fn description(&self, builder: &'e Builder<'e>) -> String {
         self.output_description(builder)
     }
+
+    fn cache_key(&self, builder: &'e Builder<'e>) -> Option<u64> {
+        // Inherited members and the inheritance graph come from other classes
+        let related = builder.hierarchy.related(&self.entity);
+        builder
+            .cache
+            .entities_key(&[self.entities(), related].concat())
+    }
 }
@@ -13,7 +13,11 @@ use crate::{
 };
 
 use super::{
-    builder::Builder, markdown::fmt_markdown, shared::fmt_autolinks, traits::EntityMethods,
+    builder::Builder,
+    export::{ApiDoc, ApiDocParam},
+    markdown::fmt_markdown,
+    shared::fmt_autolinks,
+    traits::EntityMethods,
 };
 
 struct CommentLexer<'s> {
@@ -679,7 +683,16 @@ impl<'e> JSDocComment<'e> {
                     .with_child(Html::span(&["title"], "Exceptions"))
                     .with_child(Html::div(ret.clone()))
             }))
-            // todo: see
+            .with_child_opt((!self.see.is_empty()).then(|| {
+                HtmlElement::new("section")
+                    .with_classes(&["params", "see"])
+                    .with_child(Html::span(&["title"], "See also"))
+                    .with_child(
+                        HtmlElement::new("div").with_children(
+                            self.see.iter().map(|see| self.fmt_see(see)).collect(),
+                        ),
+                    )
+            }))
             .with_children(
                 self.notes
                     .iter()
@@ -714,7 +727,106 @@ impl<'e> JSDocComment<'e> {
         .into()
     }
 
+    fn fmt_see(&self, see: &str) -> Html {
+        // Anything that looks like a link is rendered as-is
+        let see = see.trim();
+        if see.starts_with('[') || see.starts_with('<') {
+            return fmt_markdown(self.builder, see, None::<fn(_) -> _>);
+        }
+
+        // Otherwise the first word is the target and the rest is a description
+        // Unresolved targets are reported by the linter
+        let (target, desc) = see.split_once(char::is_whitespace).unwrap_or((see, ""));
+        let link = if target.contains("://") {
+            Some(target.to_owned())
+        } else {
+            self.builder
+                .root
+                .find_by_name(target)
+                .and_then(|entity| entity.abs_docs_url(self.builder.config.clone()))
+                .map(|url| url.to_string())
+        };
+
+        HtmlElement::new("p")
+            .with_child(
+                HtmlElement::new("a")
+                    .with_attr_opt("href", link)
+                    .with_child(HtmlElement::new("code").with_child(HtmlText::new(target))),
+            )
+            .with_child_opt(
+                (!desc.is_empty()).then(|| HtmlText::new(format!(" {}", desc.trim()))),
+            )
+            .into()
+    }
+
+    /// The first sentence of the description, for use in summaries
+    pub fn brief(&self) -> Option<String> {
+        let desc = self.description.as_ref()?.trim();
+        let para = desc.split("\n\n").next().unwrap_or(desc);
+        // Don't stop at the dot of an abbreviation like "e.g." or "etc."
+        let end = para.match_indices(". ").map(|(i, _)| i).find(|&i| {
+            let word = para[..i].rsplit([' ', '\n', '(']).next().unwrap_or("");
+            !["e.g", "i.e", "etc", "vs", "cf"]
+                .iter()
+                .any(|abbr| word.eq_ignore_ascii_case(abbr))
+        });
+        Some(match end {
+            Some(end) => para[..=end].to_owned(),
+            None => para.to_owned(),
+        })
+        .filter(|brief| !brief.is_empty())
+    }
+
     pub fn examples(&self) -> &Vec<Example> {
         &self.examples
     }
+
+    pub fn params(&self) -> &Vec<(String, String)> {
+        &self.params
+    }
+
+    pub fn tparams(&self) -> &Vec<(String, String)> {
+        &self.tparams
+    }
+
+    pub fn returns(&self) -> Option<&String> {
+        self.returns.as_ref()
+    }
+
+    /// The parsed fields of this comment, for the JSON export
+    pub fn export(&self) -> ApiDoc {
+        let params = |list: &Vec<(String, String)>| {
+            list.iter()
+                .map(|(name, description)| ApiDocParam {
+                    name: name.clone(),
+                    description: description.clone(),
+                })
+                .collect()
+        };
+        ApiDoc {
+            description: self.description.clone(),
+            params: params(&self.params),
+            tparams: params(&self.tparams),
+            returns: self.returns.clone(),
+            throws: self.throws.clone(),
+            see: self.see.clone(),
+            notes: self.notes.clone(),
+            short_notes: self.short_notes.clone(),
+            warnings: self.warnings.clone(),
+            version: self.version.clone(),
+            since: self.since.clone(),
+            examples: self.examples.iter().map(|ex| ex.data.clone()).collect(),
+        }
+    }
+
+    /// The symbols referred to by `@see` commands, skipping plain links
+    pub fn see_targets(&self) -> Vec<&str> {
+        self.see
+            .iter()
+            .map(|see| see.trim())
+            .filter(|see| !see.starts_with('[') && !see.starts_with('<'))
+            .filter_map(|see| see.split_whitespace().next())
+            .filter(|target| !target.contains("://"))
+            .collect()
+    }
 }
@@ -0,0 +1,137 @@
+use super::{
+    builder::Builder,
+    namespace::{CppItem, Namespace},
+    traits::{ASTEntry, Access, EntityMethods, Include},
+};
+use crate::config::Config;
+use clang::{Accessibility, Entity, EntityKind};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt::Write, sync::Arc};
+
+fn percentage(documented: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        documented as f64 / total as f64 * 100.0
+    }
+}
+
+#[derive(Default, Serialize)]
+pub struct FileCoverage {
+    pub documented: usize,
+    pub total: usize,
+    /// Fully qualified names of the items missing documentation
+    pub undocumented: Vec<String>,
+}
+
+#[derive(Default, Serialize)]
+pub struct SourceCoverage {
+    pub documented: usize,
+    pub total: usize,
+    pub files: BTreeMap<String, FileCoverage>,
+}
+
+/// How much of the public API has a doc comment, per source and per file
+#[derive(Default, Serialize)]
+pub struct Coverage {
+    pub documented: usize,
+    pub total: usize,
+    pub sources: BTreeMap<String, SourceCoverage>,
+}
+
+impl Coverage {
+    pub fn new(builder: &Builder) -> Self {
+        let mut coverage = Self::default();
+        coverage.add_namespace(&builder.root, builder.config.clone());
+        coverage
+    }
+
+    fn add_namespace(&mut self, namespace: &Namespace, config: Arc<Config>) {
+        for entry in namespace.entries.values() {
+            match entry {
+                CppItem::Namespace(ns) => self.add_namespace(ns, config.clone()),
+                CppItem::Class(_) | CppItem::Struct(_) => {
+                    let class = entry.entity();
+                    self.add(class, config.clone());
+                    for fun in class.get_member_functions(Access::Public, Include::All) {
+                        self.add(&fun, config.clone());
+                    }
+                    for field in class.get_children().iter().filter(|child| {
+                        matches!(child.get_kind(), EntityKind::FieldDecl | EntityKind::VarDecl)
+                            && child.get_accessibility() == Some(Accessibility::Public)
+                    }) {
+                        self.add(field, config.clone());
+                    }
+                }
+                _ => {
+                    for entity in entry.entities() {
+                        self.add(&entity, config.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    fn add(&mut self, entity: &Entity, config: Arc<Config>) {
+        // Stuff from external libraries isn't ours to document
+        let Some(source) = entity.config_source(config.clone()) else {
+            return;
+        };
+        let path = entity
+            .header(config)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let documented = entity.get_doc_comment().is_some() as usize;
+
+        self.documented += documented;
+        self.total += 1;
+
+        let source = self.sources.entry(source.name.clone()).or_default();
+        source.documented += documented;
+        source.total += 1;
+
+        let file = source.files.entry(path).or_default();
+        file.documented += documented;
+        file.total += 1;
+        if documented == 0 {
+            file.undocumented.push(entity.full_name().join("::"));
+        }
+    }
+
+    pub fn percentage(&self) -> f64 {
+        percentage(self.documented, self.total)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut res = String::new();
+        for (name, source) in &self.sources {
+            writeln!(
+                res,
+                "{name}: {:.1}% ({}/{})",
+                percentage(source.documented, source.total),
+                source.documented,
+                source.total
+            )
+            .unwrap();
+            for (path, file) in &source.files {
+                writeln!(
+                    res,
+                    "    {path}: {:.1}% ({}/{})",
+                    percentage(file.documented, file.total),
+                    file.documented,
+                    file.total
+                )
+                .unwrap();
+            }
+        }
+        write!(
+            res,
+            "Total: {:.1}% ({}/{})",
+            self.percentage(),
+            self.documented,
+            self.total
+        )
+        .unwrap();
+        res
+    }
+}
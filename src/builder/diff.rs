@@ -0,0 +1,478 @@
+use super::{
+    builder::Builder,
+    export::{ApiEntity, ApiExport, ApiSignature},
+    shared::fmt_section,
+    traits::{BuildResult, Entry, NavItem, OutputEntry},
+};
+use crate::{
+    html::{Html, HtmlElement, HtmlText, template::Template},
+    url::UrlPath,
+};
+use std::{collections::BTreeMap, fmt::Write, sync::Arc};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    fn verb(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "Added",
+            ChangeKind::Removed => "Removed",
+            ChangeKind::Changed => "Changed",
+        }
+    }
+}
+
+pub struct ApiChange {
+    pub kind: ChangeKind,
+    /// Kind of the entity, like `class` or `method`
+    pub entity_kind: String,
+    /// Declaration of the entity, as it is in the new version if it still
+    /// exists
+    pub declaration: String,
+    /// Docs URL of the entity in the new version
+    pub url: Option<String>,
+    pub details: Vec<String>,
+    /// Whether this likely breaks code or binaries built against the old
+    /// version
+    pub breaking: bool,
+}
+
+/// Differences between two versions of the documented API
+pub struct ApiDiff {
+    pub old_version: String,
+    pub new_version: String,
+    pub changes: Vec<ApiChange>,
+}
+
+fn fmt_signature(sig: &ApiSignature) -> String {
+    let params = sig
+        .params
+        .iter()
+        .map(|p| match (&p.ty, &p.name) {
+            (Some(ty), Some(name)) => format!("{ty} {name}"),
+            (Some(ty), None) => ty.clone(),
+            (None, name) => name.clone().unwrap_or_default(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("({params}){}", if sig.is_const { " const" } else { "" })
+}
+
+/// A one-line declaration of an entity for listing in the changelog
+fn fmt_declaration(entity: &ApiEntity) -> String {
+    let mut res = String::new();
+    if let Some(ref sig) = entity.signature {
+        if sig.is_static {
+            res += "static ";
+        }
+        if sig.is_virtual {
+            res += "virtual ";
+        }
+        if let Some(ref ret) = sig.return_type {
+            res += ret;
+            res += " ";
+        }
+        res += &entity.qualified_name;
+        res += &fmt_signature(sig);
+        if sig.is_pure_virtual {
+            res += " = 0";
+        }
+    } else if let Some(ref ty) = entity.ty
+        && matches!(entity.kind.as_str(), "field" | "variable")
+    {
+        res = format!("{ty} {}", entity.qualified_name);
+    } else {
+        res = entity.qualified_name.clone();
+    }
+    res
+}
+
+/// Entities are matched by USR, since that stays the same when the entity is
+/// moved between headers
+fn key(entity: &ApiEntity) -> String {
+    entity
+        .usr
+        .clone()
+        .unwrap_or_else(|| format!("{}:{}", entity.kind, entity.qualified_name))
+}
+
+fn by_key(export: &ApiExport) -> BTreeMap<String, &ApiEntity> {
+    // Namespaces just group the other entities, which are compared on their
+    // own
+    export
+        .entities
+        .iter()
+        .filter(|e| e.kind != "namespace")
+        .map(|e| (key(e), e))
+        .collect()
+}
+
+/// Signature without parameter names, which don't affect compatibility
+fn signature_types(sig: &ApiSignature) -> (Option<&String>, Vec<Option<&String>>, bool, bool) {
+    (
+        sig.return_type.as_ref(),
+        sig.params.iter().map(|p| p.ty.as_ref()).collect(),
+        sig.is_const,
+        sig.is_static,
+    )
+}
+
+fn compare(old: &ApiEntity, new: &ApiEntity) -> (Vec<String>, bool) {
+    let mut details = Vec::new();
+    let mut breaking = false;
+    let mut change = |detail: String, is_breaking: bool| {
+        details.push(detail);
+        breaking |= is_breaking;
+    };
+
+    match (&old.signature, &new.signature) {
+        (Some(old_sig), Some(new_sig)) => {
+            if signature_types(old_sig) != signature_types(new_sig) {
+                change(
+                    format!(
+                        "Signature changed from `{}` to `{}`",
+                        fmt_declaration(old),
+                        fmt_declaration(new)
+                    ),
+                    true,
+                );
+            } else if old_sig.params != new_sig.params {
+                change("Parameter names or default values changed".into(), false);
+            }
+            if old_sig.is_virtual && !new_sig.is_virtual {
+                change("No longer virtual".into(), true);
+            }
+            if !old_sig.is_virtual && new_sig.is_virtual {
+                change("Became virtual".into(), true);
+            }
+            if !old_sig.is_pure_virtual && new_sig.is_pure_virtual {
+                change("Became pure virtual".into(), true);
+            }
+        }
+        (None, None) => {}
+        _ => change("Signature changed".into(), true),
+    }
+
+    if old.ty != new.ty {
+        change(
+            format!(
+                "Type changed from `{}` to `{}`",
+                old.ty.as_deref().unwrap_or("?"),
+                new.ty.as_deref().unwrap_or("?")
+            ),
+            true,
+        );
+    }
+    if old.value != new.value {
+        change(
+            format!(
+                "Value changed from `{}` to `{}`",
+                old.value.as_deref().unwrap_or("?"),
+                new.value.as_deref().unwrap_or("?")
+            ),
+            // The values of enumerators get compiled into user code
+            new.kind == "enumerator",
+        );
+    }
+    if old.access != new.access {
+        change(
+            format!(
+                "Access changed from {} to {}",
+                old.access.as_deref().unwrap_or("public"),
+                new.access.as_deref().unwrap_or("public")
+            ),
+            new.access.as_deref() != Some("public"),
+        );
+    }
+    if old.bases != new.bases {
+        let names = |e: &ApiEntity| {
+            e.bases
+                .iter()
+                .map(|b| b.name.clone())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        // Any change to the bases changes the layout of the class
+        change(
+            format!(
+                "Base classes changed from `{}` to `{}`",
+                names(old),
+                names(new)
+            ),
+            true,
+        );
+    }
+    if old.template_params.len() != new.template_params.len()
+        || old
+            .template_params
+            .iter()
+            .zip(&new.template_params)
+            .any(|(a, b)| a.kind != b.kind)
+    {
+        change("Template parameters changed".into(), true);
+    }
+
+    (details, breaking)
+}
+
+impl ApiDiff {
+    pub fn new(old: &ApiExport, new: &ApiExport) -> Self {
+        let old_map = by_key(old);
+        let new_map = by_key(new);
+
+        let mut removed = old_map
+            .iter()
+            .filter(|(k, _)| !new_map.contains_key(*k))
+            .map(|(_, e)| *e)
+            .collect::<Vec<_>>();
+        let mut added = new_map
+            .iter()
+            .filter(|(k, _)| !old_map.contains_key(*k))
+            .map(|(_, e)| *e)
+            .collect::<Vec<_>>();
+
+        let mut changes = Vec::new();
+
+        // The USR of a function changes with its parameters, so a function
+        // that is removed and added again with the same name is treated as
+        // changed, unless it's ambiguous because of overloads
+        let same_name =
+            |a: &ApiEntity, b: &ApiEntity| a.kind == b.kind && a.qualified_name == b.qualified_name;
+        let mut i = 0;
+        while i < removed.len() {
+            let old_entity = removed[i];
+            let matching_old = removed.iter().filter(|e| same_name(e, old_entity)).count();
+            let matching_new = added
+                .iter()
+                .filter(|e| same_name(e, old_entity))
+                .collect::<Vec<_>>();
+            if matching_old == 1
+                && let [new_entity] = matching_new[..]
+            {
+                let (details, breaking) = compare(old_entity, new_entity);
+                changes.push(ApiChange {
+                    kind: ChangeKind::Changed,
+                    entity_kind: new_entity.kind.clone(),
+                    declaration: fmt_declaration(new_entity),
+                    url: new_entity.url.clone(),
+                    details,
+                    breaking,
+                });
+                added.retain(|e| !same_name(e, old_entity));
+                removed.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        for (k, old_entity) in &old_map {
+            if let Some(new_entity) = new_map.get(k) {
+                let (details, breaking) = compare(old_entity, new_entity);
+                if !details.is_empty() {
+                    changes.push(ApiChange {
+                        kind: ChangeKind::Changed,
+                        entity_kind: new_entity.kind.clone(),
+                        declaration: fmt_declaration(new_entity),
+                        url: new_entity.url.clone(),
+                        details,
+                        breaking,
+                    });
+                }
+            }
+        }
+
+        for entity in removed {
+            changes.push(ApiChange {
+                kind: ChangeKind::Removed,
+                entity_kind: entity.kind.clone(),
+                declaration: fmt_declaration(entity),
+                url: None,
+                details: Vec::new(),
+                breaking: true,
+            });
+        }
+
+        for entity in added {
+            // New virtual functions in existing classes change the vtable
+            let parent_existed = entity
+                .parent
+                .as_ref()
+                .is_some_and(|parent| old.entities.iter().any(|e| &e.qualified_name == parent));
+            let sig = entity.signature.as_ref();
+            let (details, breaking) = if sig.is_some_and(|s| s.is_pure_virtual) {
+                (vec!["New pure virtual function".into()], true)
+            } else if parent_existed && sig.is_some_and(|s| s.is_virtual) {
+                (
+                    vec!["New virtual function in an existing class".into()],
+                    true,
+                )
+            } else {
+                (Vec::new(), false)
+            };
+            changes.push(ApiChange {
+                kind: ChangeKind::Added,
+                entity_kind: entity.kind.clone(),
+                declaration: fmt_declaration(entity),
+                url: entity.url.clone(),
+                details,
+                breaking,
+            });
+        }
+
+        changes.sort_by(|a, b| a.declaration.cmp(&b.declaration));
+
+        Self {
+            old_version: old.project.version.clone(),
+            new_version: new.project.version.clone(),
+            changes,
+        }
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &ApiChange> {
+        self.changes.iter().filter(|c| c.breaking)
+    }
+
+    fn non_breaking(&self, kind: ChangeKind) -> impl Iterator<Item = &ApiChange> {
+        self.changes
+            .iter()
+            .filter(move |c| !c.breaking && c.kind == kind)
+    }
+
+    /// Variable name, title and changes of each section of the changelog
+    fn sections(&self) -> Vec<(&'static str, &'static str, Vec<&ApiChange>)> {
+        vec![
+            ("breaking", "Breaking changes", self.breaking().collect()),
+            (
+                "added",
+                "Added",
+                self.non_breaking(ChangeKind::Added).collect(),
+            ),
+            (
+                "changed",
+                "Changed",
+                self.non_breaking(ChangeKind::Changed).collect(),
+            ),
+        ]
+    }
+
+    pub fn summary(&self) -> String {
+        let count = |kind| self.changes.iter().filter(|c| c.kind == kind).count();
+        format!(
+            "{} breaking changes, {} additions, {} removals and {} other changes",
+            self.breaking().count(),
+            count(ChangeKind::Added),
+            count(ChangeKind::Removed),
+            count(ChangeKind::Changed)
+        )
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut res = format!(
+            "# API changes from {} to {}\n\n{}\n",
+            self.old_version,
+            self.new_version,
+            self.summary()
+        );
+        for (_, title, changes) in self.sections() {
+            if changes.is_empty() {
+                continue;
+            }
+            write!(res, "\n## {title}\n\n").unwrap();
+            for change in changes {
+                write!(
+                    res,
+                    "- {} {} `{}`",
+                    change.kind.verb(),
+                    change.entity_kind,
+                    change.declaration
+                )
+                .unwrap();
+                if !change.details.is_empty() {
+                    write!(res, ": {}", change.details.join("; ")).unwrap();
+                }
+                res.push('\n');
+            }
+        }
+        res
+    }
+
+    fn fmt_change(change: &ApiChange) -> Html {
+        let verb = change.kind.verb();
+        HtmlElement::new("div")
+            .with_class("change")
+            .with_child(Html::span(&["badge", &verb.to_lowercase()], verb))
+            .with_child(HtmlText::new(format!(" {} ", change.entity_kind)))
+            .with_child(
+                HtmlElement::new("a")
+                    .with_attr_opt("href", change.url.clone())
+                    .with_child(HtmlElement::new("code").with_text(&change.declaration)),
+            )
+            .with_children(change.details.iter().map(Html::p).collect())
+            .into()
+    }
+}
+
+/// Docs page listing the changes to the API since a previous version
+pub struct Changelog {
+    diff: ApiDiff,
+}
+
+impl Changelog {
+    pub fn new(diff: ApiDiff) -> Self {
+        Self { diff }
+    }
+}
+
+impl<'e> Entry<'e> for Changelog {
+    fn name(&self) -> String {
+        "Changelog".into()
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("changelog")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("git-commit", false)))
+    }
+}
+
+impl<'e> OutputEntry<'e> for Changelog {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
+        let mut vars = vec![
+            ("old_version", HtmlText::new(&self.diff.old_version).into()),
+            ("new_version", HtmlText::new(&self.diff.new_version).into()),
+            ("summary", HtmlText::new(self.diff.summary()).into()),
+        ];
+        for (name, title, changes) in self.diff.sections() {
+            vars.push((
+                name,
+                fmt_section(
+                    title,
+                    changes.into_iter().map(ApiDiff::fmt_change).collect(),
+                ),
+            ));
+        }
+        (builder.config.templates.changelog.clone(), vars)
+    }
+
+    fn markdown(&self, _builder: &'e Builder<'e>) -> String {
+        self.diff.to_markdown()
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Changes to the {} API from {} to {}",
+            builder.config.project.name, self.diff.old_version, self.diff.new_version
+        )
+    }
+}
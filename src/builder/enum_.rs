@@ -0,0 +1,78 @@
+use super::{
+    builder::Builder,
+    export::page_context,
+    markdown_output::markdown_enum,
+    shared::output_enum,
+    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
+};
+use crate::{
+    html::{Html, template::Template},
+    url::UrlPath,
+};
+use clang::Entity;
+use std::sync::Arc;
+
+pub struct Enum<'e> {
+    entity: Entity<'e>,
+}
+
+impl<'e> Enum<'e> {
+    pub fn new(entity: Entity<'e>) -> Self {
+        Self { entity }
+    }
+}
+
+impl<'e> Entry<'e> for Enum<'e> {
+    fn name(&self) -> String {
+        self.entity
+            .get_name()
+            .unwrap_or("`Anonymous enum`".into())
+    }
+
+    fn url(&self) -> UrlPath {
+        self.entity.rel_docs_url().expect("Unable to get enum URL")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("list", false)))
+    }
+}
+
+impl<'e> ASTEntry<'e> for Enum<'e> {
+    fn entity(&self) -> &Entity<'e> {
+        &self.entity
+    }
+
+    fn category(&self) -> &'static str {
+        "enum"
+    }
+}
+
+impl<'e> OutputEntry<'e> for Enum<'e> {
+    fn output(&self, builder: &Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.enum_.clone(),
+            output_enum(self, builder),
+        )
+    }
+
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        markdown_enum(self, builder)
+    }
+
+    fn context(&self, builder: &'e Builder<'e>) -> Option<serde_json::Value> {
+        Some(page_context(self, builder))
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        self.output_description(builder)
+    }
+
+    fn cache_key(&self, builder: &'e Builder<'e>) -> Option<u64> {
+        builder.cache.entities_key(&self.entities())
+    }
+}
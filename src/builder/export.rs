@@ -0,0 +1,508 @@
+use super::{
+    builder::Builder,
+    comment::JSDocComment,
+    namespace::{CppItem, Namespace},
+    shared::{
+        fmt_enumerator_value, get_aliased_type, get_fields, get_inherited_members,
+        get_macro_params_and_body, get_variable_initializer,
+    },
+    traits::{ASTEntry, Access, EntityMethods, Include},
+};
+use clang::{Accessibility, Entity, EntityKind, token::TokenKind};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{fs, path::Path};
+
+/// Version of the export format. Bumped whenever the format changes in a way
+/// that could break tools reading it
+pub const API_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiParam {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    pub default: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiSignature {
+    pub return_type: Option<String>,
+    pub params: Vec<ApiParam>,
+    pub is_static: bool,
+    pub is_virtual: bool,
+    pub is_pure_virtual: bool,
+    pub is_const: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiTemplateParam {
+    /// One of `type`, `value` or `template`
+    pub kind: String,
+    pub name: Option<String>,
+    pub declaration: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiBase {
+    pub name: String,
+    pub usr: Option<String>,
+    pub access: Option<String>,
+    pub is_virtual: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiLocation {
+    /// Path of the header relative to the project root
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiDocParam {
+    pub name: String,
+    pub description: String,
+}
+
+/// The parsed fields of a doc comment
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiDoc {
+    pub description: Option<String>,
+    pub params: Vec<ApiDocParam>,
+    pub tparams: Vec<ApiDocParam>,
+    pub returns: Option<String>,
+    pub throws: Option<String>,
+    pub see: Vec<String>,
+    pub notes: Vec<String>,
+    pub short_notes: Vec<String>,
+    pub warnings: Vec<String>,
+    pub version: Option<String>,
+    pub since: Option<String>,
+    pub examples: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiEntity {
+    /// One of `namespace`, `class`, `struct`, `function`, `method`, `field`,
+    /// `enum`, `enumerator`, `alias`, `variable` or `macro`
+    pub kind: String,
+    pub name: String,
+    pub qualified_name: String,
+    pub usr: Option<String>,
+    /// Qualified name of the class or namespace this is declared in
+    pub parent: Option<String>,
+    pub access: Option<String>,
+    /// Type of fields and variables, underlying type of aliases and enums
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    /// Value of enumerators and constants, body of macros
+    pub value: Option<String>,
+    pub signature: Option<ApiSignature>,
+    pub template_params: Vec<ApiTemplateParam>,
+    pub bases: Vec<ApiBase>,
+    pub location: Option<ApiLocation>,
+    pub include: Option<String>,
+    pub url: Option<String>,
+    pub doc: Option<ApiDoc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ApiProject {
+    pub name: String,
+    pub version: String,
+}
+
+/// Everything Flash knows about the documented API
+#[derive(Serialize, Deserialize)]
+pub struct ApiExport {
+    pub schema_version: u32,
+    pub project: ApiProject,
+    pub entities: Vec<ApiEntity>,
+}
+
+fn access_name(access: Accessibility) -> String {
+    match access {
+        Accessibility::Public => "public",
+        Accessibility::Protected => "protected",
+        Accessibility::Private => "private",
+    }
+    .into()
+}
+
+fn get_param_default(param: &Entity) -> Option<String> {
+    // Only look at the `=` token itself, since the type or the default can
+    // contain a `=` inside a string literal or an operator like `==`
+    let range = param.get_range()?;
+    let eq = range
+        .tokenize()
+        .into_iter()
+        .find(|token| token.get_kind() == TokenKind::Punctuation && token.get_spelling() == "=")?;
+    let start = eq.get_range().get_end().get_file_location();
+    let end = range.get_end().get_file_location();
+    let contents = start.file?.get_contents()?;
+    let default = contents.get(start.offset as usize..end.offset as usize)?;
+    Some(default.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn export_signature(entity: &Entity) -> ApiSignature {
+    ApiSignature {
+        return_type: entity.get_result_type().map(|t| t.get_display_name()),
+        params: entity
+            .get_function_arguments()
+            .unwrap_or_default()
+            .iter()
+            .map(|arg| ApiParam {
+                name: arg.get_name(),
+                ty: arg.get_type().map(|t| t.get_display_name()),
+                default: get_param_default(arg),
+            })
+            .collect(),
+        is_static: entity.is_static_method(),
+        is_virtual: entity.is_virtual_method(),
+        is_pure_virtual: entity.is_pure_virtual_method(),
+        is_const: entity.is_const_method(),
+    }
+}
+
+fn export_template_params(entity: &Entity) -> Vec<ApiTemplateParam> {
+    entity
+        .get_children()
+        .iter()
+        .filter_map(|child| {
+            let kind = match child.get_kind() {
+                EntityKind::TemplateTypeParameter => "type",
+                EntityKind::NonTypeTemplateParameter => "value",
+                EntityKind::TemplateTemplateParameter => "template",
+                _ => return None,
+            };
+            Some(ApiTemplateParam {
+                kind: kind.into(),
+                name: child.get_name(),
+                declaration: child.extract_source_string_cleaned(),
+            })
+        })
+        .collect()
+}
+
+fn export_bases(entity: &Entity) -> Vec<ApiBase> {
+    entity
+        .get_children()
+        .iter()
+        .filter(|child| child.get_kind() == EntityKind::BaseSpecifier)
+        .map(|base| ApiBase {
+            name: base
+                .get_type()
+                .map(|t| t.get_display_name())
+                .unwrap_or_else(|| base.get_display_name().unwrap_or_default()),
+            usr: base
+                .get_definition()
+                .and_then(|def| def.get_usr())
+                .map(|usr| usr.0),
+            access: base.get_accessibility().map(access_name),
+            is_virtual: base.is_virtual_base(),
+        })
+        .collect()
+}
+
+struct Exporter<'e> {
+    builder: &'e Builder<'e>,
+    entities: Vec<ApiEntity>,
+}
+
+impl ApiEntity {
+    pub fn new(entity: &Entity, kind: &str, builder: &Builder) -> Self {
+        let config = builder.config.clone();
+        let full_name = entity.full_name();
+
+        let (ty, value, signature) = match entity.get_kind() {
+            EntityKind::FieldDecl | EntityKind::VarDecl => (
+                entity.get_type().map(|t| t.get_display_name()),
+                get_variable_initializer(entity),
+                None,
+            ),
+            EntityKind::TypedefDecl
+            | EntityKind::TypeAliasDecl
+            | EntityKind::TypeAliasTemplateDecl => (
+                get_aliased_type(entity).map(|t| t.get_display_name()),
+                None,
+                None,
+            ),
+            EntityKind::EnumDecl => (
+                entity
+                    .get_enum_underlying_type()
+                    .map(|t| t.get_display_name()),
+                None,
+                None,
+            ),
+            EntityKind::EnumConstantDecl => (None, fmt_enumerator_value(entity), None),
+            EntityKind::MacroDefinition => {
+                let (params, body) = get_macro_params_and_body(entity).unwrap_or((None, None));
+                (
+                    None,
+                    body,
+                    params.map(|params| ApiSignature {
+                        return_type: None,
+                        params: params
+                            .into_iter()
+                            .map(|name| ApiParam {
+                                name: Some(name),
+                                ty: None,
+                                default: None,
+                            })
+                            .collect(),
+                        is_static: false,
+                        is_virtual: false,
+                        is_pure_virtual: false,
+                        is_const: false,
+                    }),
+                )
+            }
+            _ => (
+                None,
+                None,
+                entity
+                    .get_function_arguments()
+                    .is_some()
+                    .then(|| export_signature(entity)),
+            ),
+        };
+
+        Self {
+            kind: kind.into(),
+            name: entity.get_name().unwrap_or_default(),
+            qualified_name: full_name.join("::"),
+            usr: entity.get_usr().map(|usr| usr.0),
+            parent: (full_name.len() > 1).then(|| full_name[..full_name.len() - 1].join("::")),
+            access: entity.get_accessibility().map(access_name),
+            ty,
+            value,
+            signature,
+            template_params: export_template_params(entity),
+            bases: export_bases(entity),
+            location: entity.get_location().and_then(|loc| {
+                let loc = loc.get_file_location();
+                Some(ApiLocation {
+                    file: entity
+                        .header(config.clone())?
+                        .to_string_lossy()
+                        .replace('\\', "/"),
+                    line: loc.line,
+                    column: loc.column,
+                })
+            }),
+            include: entity
+                .include_path(config.clone())
+                .map(|p| p.to_raw_string()),
+            url: entity.abs_docs_url(config).map(|url| url.to_string()),
+            doc: entity
+                .get_doc_comment()
+                .map(|c| JSDocComment::parse(c, builder).export()),
+        }
+    }
+}
+
+impl<'e> Exporter<'e> {
+    fn add(&mut self, entity: &Entity<'e>, kind: &str) {
+        self.entities
+            .push(ApiEntity::new(entity, kind, self.builder));
+    }
+
+    fn add_namespace(&mut self, namespace: &'e Namespace<'e>) {
+        for entry in namespace.entries.values() {
+            match entry {
+                CppItem::Namespace(ns) => {
+                    self.add(entry.entity(), "namespace");
+                    self.add_namespace(ns);
+                }
+                CppItem::Class(_) | CppItem::Struct(_) => {
+                    let class = entry.entity();
+                    self.add(
+                        class,
+                        if matches!(entry, CppItem::Class(_)) {
+                            "class"
+                        } else {
+                            "struct"
+                        },
+                    );
+                    for fun in class.get_member_functions(Access::All, Include::All) {
+                        self.add(&fun, "method");
+                    }
+                    for field in class.get_children().iter().filter(|child| {
+                        matches!(
+                            child.get_kind(),
+                            EntityKind::FieldDecl | EntityKind::VarDecl
+                        ) && matches!(
+                            child.get_accessibility(),
+                            Some(Accessibility::Public | Accessibility::Protected)
+                        )
+                    }) {
+                        self.add(field, "field");
+                    }
+                }
+                CppItem::Enum(_) => {
+                    self.add(entry.entity(), "enum");
+                    for value in entry
+                        .entity()
+                        .get_children()
+                        .iter()
+                        .filter(|child| child.get_kind() == EntityKind::EnumConstantDecl)
+                    {
+                        self.add(value, "enumerator");
+                    }
+                }
+                CppItem::Function(_) => {
+                    for fun in entry.entities() {
+                        self.add(&fun, "function");
+                    }
+                }
+                CppItem::Alias(_) => self.add(entry.entity(), "alias"),
+                CppItem::Variable(_) => self.add(entry.entity(), "variable"),
+                CppItem::Macro(_) => self.add(entry.entity(), "macro"),
+            }
+        }
+    }
+}
+
+impl ApiExport {
+    /// Read an export written by a previous build
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let export: Self = serde_json::from_str(
+            &fs::read_to_string(path)
+                .map_err(|e| format!("Unable to read {}: {e}", path.to_string_lossy()))?,
+        )
+        .map_err(|e| format!("Unable to parse {}: {e}", path.to_string_lossy()))?;
+        if export.schema_version > API_SCHEMA_VERSION {
+            return Err(format!(
+                "{} uses a newer export format (version {}) than this version of Flash supports",
+                path.to_string_lossy(),
+                export.schema_version
+            ));
+        }
+        Ok(export)
+    }
+
+    pub fn new<'e>(builder: &'e Builder<'e>) -> Self {
+        let mut exporter = Exporter {
+            builder,
+            entities: Vec::new(),
+        };
+        exporter.add_namespace(&builder.root);
+
+        // Namespace entries are stored in a map, so sort for stable output
+        let mut entities = exporter.entities;
+        entities.sort_by(|a, b| {
+            a.qualified_name
+                .cmp(&b.qualified_name)
+                .then_with(|| a.usr.cmp(&b.usr))
+        });
+
+        Self {
+            schema_version: API_SCHEMA_VERSION,
+            project: ApiProject {
+                name: builder.config.project.name.clone(),
+                version: builder.config.project.version.clone(),
+            },
+            entities,
+        }
+    }
+}
+
+fn classlike_kind(class: &Entity) -> &'static str {
+    match class.get_kind() {
+        EntityKind::StructDecl => "struct",
+        _ => "class",
+    }
+}
+
+/// Structured data about an entry's page for templates using the Jinja
+/// engine. Members are grouped the same way as on the default pages
+pub fn page_context<'e>(entry: &dyn ASTEntry<'e>, builder: &'e Builder<'e>) -> serde_json::Value {
+    let entity = entry.entity();
+    let entities = |list: Vec<Entity>, kind: &str| {
+        list.iter()
+            .map(|e| ApiEntity::new(e, kind, builder))
+            .collect::<Vec<_>>()
+    };
+
+    let mut context = json!({
+        "entity": ApiEntity::new(entity, entry.category(), builder),
+    });
+    match entry.category() {
+        "class" | "struct" => {
+            context["members"] = json!({
+                "public_static_functions": entities(
+                    entity.get_member_functions(Access::Public, Include::Statics),
+                    "method",
+                ),
+                "public_static_members": entities(
+                    get_fields(entity, EntityKind::VarDecl, Accessibility::Public),
+                    "field",
+                ),
+                "public_member_functions": entities(
+                    entity.get_member_functions(Access::Public, Include::Members),
+                    "method",
+                ),
+                "public_members": entities(
+                    get_fields(entity, EntityKind::FieldDecl, Accessibility::Public),
+                    "field",
+                ),
+                "protected_member_functions": entities(
+                    entity.get_member_functions(Access::Protected, Include::Members),
+                    "method",
+                ),
+                "protected_static_members": entities(
+                    get_fields(entity, EntityKind::VarDecl, Accessibility::Protected),
+                    "field",
+                ),
+                "protected_members": entities(
+                    get_fields(entity, EntityKind::FieldDecl, Accessibility::Protected),
+                    "field",
+                ),
+            });
+            context["derived"] = json!(
+                builder
+                    .hierarchy
+                    .derived(entity)
+                    .iter()
+                    .map(|d| ApiEntity::new(d, classlike_kind(d), builder))
+                    .collect::<Vec<_>>()
+            );
+            context["inherited"] = json!(
+                get_inherited_members(entity)
+                    .into_iter()
+                    .map(|inherited| {
+                        json!({
+                            "base": ApiEntity::new(
+                                &inherited.base,
+                                classlike_kind(&inherited.base),
+                                builder,
+                            ),
+                            "functions": entities(
+                                inherited.functions.into_iter().map(|(f, _)| f).collect(),
+                                "method",
+                            ),
+                            "fields": entities(
+                                inherited.fields.into_iter().map(|(f, _)| f).collect(),
+                                "field",
+                            ),
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            );
+        }
+        "function" => context["overloads"] = json!(entities(entry.entities(), "function")),
+        "enum" => {
+            context["enumerators"] = json!(entities(
+                entity
+                    .get_children()
+                    .into_iter()
+                    .filter(|child| child.get_kind() == EntityKind::EnumConstantDecl)
+                    .collect(),
+                "enumerator",
+            ))
+        }
+        _ => {}
+    }
+    context
+}
@@ -1,15 +1,22 @@
 use super::{
     builder::Builder,
+    cache::hash_of,
+    markdown_output::MarkdownPage,
     namespace::CppItemKind,
-    shared::{fmt_class_method, fmt_classlike_decl, fmt_section},
+    shared::{fmt_class_method, fmt_classlike_decl, fmt_macro, fmt_section},
     traits::{ASTEntry, BuildResult, Entry, NavItem, OutputEntry},
 };
 use crate::{
     config::{Config, Source},
-    html::{Html, HtmlText},
+    html::{Html, HtmlText, template::Template},
     url::UrlPath,
 };
-use std::{collections::HashMap, path::Path, sync::Arc};
+use clang::Entity;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 pub struct File {
     source: Arc<Source>,
@@ -20,6 +27,35 @@ impl File {
     pub fn new(def: Arc<Source>, path: UrlPath) -> Self {
         Self { source: def, path }
     }
+
+    /// The path the file is included with
+    pub fn include_path(&self) -> String {
+        self.source.dir.join(&self.path).to_raw_string()
+    }
+
+    /// Where the file is on disk
+    pub fn abs_path(&self, config: &Config) -> PathBuf {
+        config.input_dir.join(self.include_path())
+    }
+
+    /// Whether an entity is declared in this file
+    fn declares(&self, entity: &Entity, builder: &Builder) -> bool {
+        entity
+            .get_location()
+            .and_then(|file| file.get_file_location().file)
+            .is_some_and(|file| file.get_path() == self.abs_path(&builder.config))
+    }
+
+    /// Entries of a category that are declared in this file
+    fn entries<'e>(&self, builder: &'e Builder<'e>, category: &str) -> Vec<&'e dyn ASTEntry<'e>> {
+        builder.root.get(&|entry| {
+            entry.category() == category
+                && entry
+                    .entities()
+                    .iter()
+                    .any(|entity| self.declares(entity, builder))
+        })
+    }
 }
 
 impl<'e> Entry<'e> for File {
@@ -36,25 +72,13 @@ impl<'e> Entry<'e> for File {
     }
 
     fn nav(&self) -> NavItem {
-        NavItem::new_link(&self.name(), self.url(), Some(("file", false)), Vec::new())
+        NavItem::new_link(&self.name(), self.url(), Some(("file", false)))
     }
 }
 
 impl<'e> OutputEntry<'e> for File {
-    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
-        let matcher = |entry: &dyn ASTEntry<'e>| -> bool {
-            entry
-                .entity()
-                .get_location()
-                .and_then(|file| file.get_file_location().file)
-                .is_some_and(|file| {
-                    file.get_path()
-                        == builder
-                            .config
-                            .input_dir
-                            .join(self.source.dir.join(&self.path).to_raw_string())
-                })
-        };
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
+        let matcher = |entity: &Entity<'e>| -> bool { self.declares(entity, builder) };
 
         (
             builder.config.templates.file.clone(),
@@ -77,9 +101,22 @@ impl<'e> OutputEntry<'e> for File {
                     )
                     .into(),
                 ),
+                ("file_path", HtmlText::new(self.include_path()).into()),
                 (
-                    "file_path",
-                    HtmlText::new(self.source.dir.join(&self.path).to_raw_string()).into(),
+                    "include_cost",
+                    builder.includes.fmt_cost(&self.abs_path(&builder.config)),
+                ),
+                (
+                    "includes",
+                    builder
+                        .includes
+                        .fmt_includes(&self.abs_path(&builder.config), builder),
+                ),
+                (
+                    "included_by",
+                    builder
+                        .includes
+                        .fmt_included_by(&self.abs_path(&builder.config), builder),
                 ),
                 (
                     "functions",
@@ -91,10 +128,12 @@ impl<'e> OutputEntry<'e> for File {
                                 matches!(
                                     CppItemKind::from(entry.entity()),
                                     Some(CppItemKind::Function)
-                                ) && matcher(entry)
+                                ) && entry.entities().iter().any(matcher)
                             })
                             .into_iter()
-                            .map(|fun| fmt_class_method(fun.entity(), builder))
+                            .flat_map(|fun| fun.entities())
+                            .filter(matcher)
+                            .map(|fun| fmt_class_method(&fun, builder))
                             .collect(),
                     ),
                 ),
@@ -108,7 +147,7 @@ impl<'e> OutputEntry<'e> for File {
                                 matches!(
                                     CppItemKind::from(entry.entity()),
                                     Some(CppItemKind::Class)
-                                ) && matcher(entry)
+                                ) && matcher(entry.entity())
                             })
                             .into_iter()
                             .map(|cls| fmt_classlike_decl(cls.entity(), "class", builder))
@@ -125,23 +164,117 @@ impl<'e> OutputEntry<'e> for File {
                                 matches!(
                                     CppItemKind::from(entry.entity()),
                                     Some(CppItemKind::Struct)
-                                ) && matcher(entry)
+                                ) && matcher(entry.entity())
                             })
                             .into_iter()
                             .map(|cls| fmt_classlike_decl(cls.entity(), "struct", builder))
                             .collect(),
                     ),
                 ),
+                (
+                    "enums",
+                    fmt_section(
+                        "Enums",
+                        builder
+                            .root
+                            .get(&|entry| {
+                                matches!(
+                                    CppItemKind::from(entry.entity()),
+                                    Some(CppItemKind::Enum)
+                                ) && matcher(entry.entity())
+                            })
+                            .into_iter()
+                            .map(|en| fmt_classlike_decl(en.entity(), "enum", builder))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "macros",
+                    fmt_section(
+                        "Macros",
+                        builder
+                            .root
+                            .get(&|entry| {
+                                matches!(
+                                    CppItemKind::from(entry.entity()),
+                                    Some(CppItemKind::Macro)
+                                ) && matcher(entry.entity())
+                            })
+                            .into_iter()
+                            .map(|mac| fmt_macro(mac.entity(), builder))
+                            .collect(),
+                    ),
+                ),
             ],
         )
     }
 
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        let mut page = MarkdownPage::new(builder, self.url());
+        page.heading(1, &format!("File `{}`", self.name()));
+        let path = self.source.dir.join(&self.path);
+        match builder.config.project.tree {
+            Some(ref tree) => page.push(&format!("[`{}`]({tree}{path})", path.to_raw_string())),
+            None => page.push(&format!("`{}`", path.to_raw_string())),
+        }
+        let graph = &builder.includes;
+        let abs_path = self.abs_path(&builder.config);
+        if let Some(summary) = graph.cost_summary(&abs_path) {
+            page.push(&summary);
+        }
+
+        page.section(
+            "Functions",
+            self.entries(builder, "function")
+                .into_iter()
+                .flat_map(|fun| fun.entities())
+                .filter(|fun| self.declares(fun, builder))
+                .collect(),
+            MarkdownPage::method,
+        );
+        for (title, category) in [
+            ("Classes", "class"),
+            ("Structs", "struct"),
+            ("Enums", "enum"),
+            ("Macros", "macro"),
+        ] {
+            page.entry_list(title, self.entries(builder, category));
+        }
+
+        page.section(
+            "Includes",
+            graph.includes(&abs_path).iter().collect(),
+            |page, include| {
+                let line = graph.markdown_file(&include.path, &include.directive(), page);
+                page.list_item(&line);
+            },
+        );
+        page.section(
+            "Included by",
+            graph.included_by(&abs_path),
+            |page, includer| {
+                let line = graph.markdown_file(includer, &graph.name(includer), page);
+                page.list_item(&line);
+            },
+        );
+        page.finish()
+    }
+
     fn description(&self, builder: &'e Builder<'e>) -> String {
         format!(
             "Documentation for {} in {}",
             self.path, builder.config.project.name
         )
     }
+
+    fn cache_key(&self, builder: &'e Builder<'e>) -> Option<u64> {
+        let path = self.abs_path(&builder.config);
+        // The include sections depend on the other headers too
+        Some(hash_of(&(
+            builder.cache.file_hash(&path)?,
+            builder.includes.key(&path),
+        )))
+    }
 }
 
 pub struct Dir {
@@ -161,6 +294,15 @@ impl Dir {
             files: HashMap::new(),
         }
     }
+
+    /// Every file in the directory and its subdirectories
+    pub fn all_files(&self) -> Vec<&File> {
+        self.dirs
+            .values()
+            .flat_map(|dir| dir.all_files())
+            .chain(self.files.values())
+            .collect()
+    }
 }
 
 impl<'e> Entry<'e> for Dir {
@@ -1,33 +1,63 @@
 use std::sync::Arc;
 
-use crate::{html::Html, url::UrlPath};
+use crate::{
+    html::{Html, template::Template},
+    url::UrlPath,
+};
 use clang::Entity;
 
 use super::{
     builder::Builder,
+    export::page_context,
+    markdown_output::markdown_function,
     shared::output_function,
     traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
 };
 
 pub struct Function<'e> {
-    entity: Entity<'e>,
+    /// All the overloads of this function, in declaration order. Always has
+    /// at least one entity
+    overloads: Vec<Entity<'e>>,
 }
 
 impl<'e> Function<'e> {
     pub fn new(entity: Entity<'e>) -> Self {
-        Self { entity }
+        Self {
+            overloads: vec![entity],
+        }
+    }
+
+    pub fn add_overload(&mut self, entity: Entity<'e>) {
+        // The same function may be redeclared multiple times
+        if !self
+            .overloads
+            .iter()
+            .any(|o| o.get_usr().is_some() && o.get_usr() == entity.get_usr())
+        {
+            self.overloads.push(entity);
+        }
+    }
+
+    pub fn merge_with_function(&mut self, other: Function<'e>) {
+        for entity in other.overloads {
+            self.add_overload(entity);
+        }
+    }
+
+    pub fn overloads(&self) -> &[Entity<'e>] {
+        &self.overloads
     }
 }
 
 impl<'e> Entry<'e> for Function<'e> {
     fn name(&self) -> String {
-        self.entity
+        self.entity()
             .get_name()
             .unwrap_or("`Anonymous function`".into())
     }
 
     fn url(&self) -> UrlPath {
-        self.entity
+        self.entity()
             .rel_docs_url()
             .expect("Unable to get function URL")
     }
@@ -37,13 +67,17 @@ impl<'e> Entry<'e> for Function<'e> {
     }
 
     fn nav(&self) -> NavItem {
-        NavItem::new_link(&self.name(), self.url(), Some(("code", true)), Vec::new())
+        NavItem::new_link(&self.name(), self.url(), Some(("code", true)))
     }
 }
 
 impl<'e> ASTEntry<'e> for Function<'e> {
     fn entity(&self) -> &Entity<'e> {
-        &self.entity
+        &self.overloads[0]
+    }
+
+    fn entities(&self) -> Vec<Entity<'e>> {
+        self.overloads.clone()
     }
 
     fn category(&self) -> &'static str {
@@ -52,14 +86,26 @@ impl<'e> ASTEntry<'e> for Function<'e> {
 }
 
 impl<'e> OutputEntry<'e> for Function<'e> {
-    fn output(&self, builder: &Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+    fn output(&self, builder: &Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
         (
             builder.config.templates.function.clone(),
             output_function(self, builder),
         )
     }
 
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        markdown_function(self, builder)
+    }
+
+    fn context(&self, builder: &'e Builder<'e>) -> Option<serde_json::Value> {
+        Some(page_context(self, builder))
+    }
+
     fn description(&self, builder: &'e Builder<'e>) -> String {
         self.output_description(builder)
     }
+
+    fn cache_key(&self, builder: &'e Builder<'e>) -> Option<u64> {
+        builder.cache.entities_key(&self.entities())
+    }
 }
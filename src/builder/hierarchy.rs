@@ -0,0 +1,532 @@
+use super::{
+    builder::Builder,
+    markdown_output::MarkdownPage,
+    namespace::{CppItem, Namespace},
+    shared::get_bases,
+    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
+};
+use crate::{
+    html::{Html, HtmlElement, HtmlList, HtmlText, template::Template},
+    url::UrlPath,
+};
+use clang::Entity;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// How many levels of derived classes the graph on a class page shows
+const DERIVED_DEPTH: usize = 2;
+/// Rows of the graph with more classes than this are cut short
+const MAX_ROW_LEN: usize = 8;
+
+const NODE_HEIGHT: usize = 28;
+const NODE_PADDING: usize = 12;
+/// Roughly the width of a character in the graph's monospace font
+const CHAR_WIDTH: usize = 8;
+const H_GAP: usize = 16;
+const V_GAP: usize = 36;
+const MARGIN: usize = 4;
+
+/// Styles for the graph. The colors follow the theme when the graph is on a
+/// docs page and fall back to the default theme anywhere else
+const GRAPH_STYLE: &str = "\
+.inheritance-graph { font-family: 'Source Code Pro', monospace; font-size: 13px; }
+.inheritance-graph rect { fill: var(--flash-darker, #3a3a3a); stroke: var(--flash-border, #555); stroke-width: 1.5; }
+.inheritance-graph .current rect { stroke: var(--flash-green, #9ff4b7); }
+.inheritance-graph .external rect, .inheritance-graph .more rect { stroke-dasharray: 4 3; }
+.inheritance-graph text { fill: var(--flash-light, #aaa); text-anchor: middle; dominant-baseline: central; }
+.inheritance-graph a:hover rect { fill: var(--flash-hover, #444); }
+.inheritance-graph line { stroke: var(--flash-border, #555); stroke-width: 1.5; }
+.inheritance-graph marker path { fill: var(--flash-darker, #3a3a3a); stroke: var(--flash-border, #555); }";
+
+fn usr_of(entity: &Entity) -> Option<String> {
+    entity.get_usr().map(|usr| usr.0)
+}
+
+fn collect_classes<'e>(ns: &Namespace<'e>, out: &mut Vec<Entity<'e>>) {
+    for entry in ns.entries.values() {
+        match entry {
+            CppItem::Namespace(ns) => collect_classes(ns, out),
+            CppItem::Class(class) => out.push(*class.entity()),
+            CppItem::Struct(class) => out.push(*class.entity()),
+            _ => {}
+        }
+    }
+}
+
+/// A class in the inheritance graph
+pub struct ClassNode<'e> {
+    pub entity: Entity<'e>,
+    /// Whether the class has a page in the docs. Bases from outside the
+    /// documented headers are still part of the graph
+    pub documented: bool,
+    /// USRs of the direct bases
+    bases: Vec<String>,
+    /// USRs of the classes that directly inherit from this one, sorted by name
+    derived: Vec<String>,
+}
+
+impl ClassNode<'_> {
+    fn full_name(&self) -> String {
+        self.entity.full_name().join("::")
+    }
+}
+
+/// A box in the graph of a class
+struct GraphBox<'a> {
+    usr: Option<&'a str>,
+    label: String,
+    x: usize,
+    y: usize,
+    width: usize,
+}
+
+/// Which classes inherit from which. Built once for the whole build so class
+/// pages don't need to search every class for the ones deriving from them
+pub struct Hierarchy<'e> {
+    nodes: HashMap<String, ClassNode<'e>>,
+}
+
+impl<'e> Hierarchy<'e> {
+    pub fn new(root: &Namespace<'e>) -> Self {
+        let mut classes = Vec::new();
+        collect_classes(root, &mut classes);
+
+        let mut nodes = HashMap::new();
+        let mut queue = Vec::new();
+        for class in classes {
+            let Some(usr) = usr_of(&class) else {
+                continue;
+            };
+            nodes.insert(
+                usr.clone(),
+                ClassNode {
+                    entity: class,
+                    documented: true,
+                    bases: Vec::new(),
+                    derived: Vec::new(),
+                },
+            );
+            queue.push(usr);
+        }
+
+        // Walk up from the documented classes, adding the bases that aren't
+        // documented as they come up
+        while let Some(usr) = queue.pop() {
+            let bases = get_bases(&nodes[&usr].entity)
+                .into_iter()
+                .filter_map(|(base, _)| Some((usr_of(&base)?, base)))
+                .collect::<Vec<_>>();
+            for (base_usr, base) in &bases {
+                let node = nodes.entry(base_usr.clone()).or_insert_with(|| {
+                    queue.push(base_usr.clone());
+                    ClassNode {
+                        entity: *base,
+                        documented: false,
+                        bases: Vec::new(),
+                        derived: Vec::new(),
+                    }
+                });
+                node.derived.push(usr.clone());
+            }
+            nodes.get_mut(&usr).unwrap().bases = bases.into_iter().map(|(usr, _)| usr).collect();
+        }
+
+        let names = nodes
+            .iter()
+            .map(|(usr, node)| (usr.clone(), node.full_name()))
+            .collect::<HashMap<_, _>>();
+        for node in nodes.values_mut() {
+            node.derived.sort_by_key(|usr| &names[usr]);
+        }
+
+        Self { nodes }
+    }
+
+    fn node(&self, class: &Entity) -> Option<&ClassNode<'e>> {
+        self.nodes.get(&usr_of(class)?)
+    }
+
+    /// Documented classes that directly inherit from a class, sorted by name
+    pub fn derived(&self, class: &Entity) -> Vec<Entity<'e>> {
+        self.node(class)
+            .map(|node| {
+                node.derived
+                    .iter()
+                    .map(|usr| &self.nodes[usr])
+                    .filter(|derived| derived.documented)
+                    .map(|derived| derived.entity)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Classes reachable from a class through its bases (`up`) or derived
+    /// classes, grouped by distance. Classes reachable through several paths
+    /// are placed by the longest one so every edge points the same way
+    fn layers(&self, usr: &str, up: bool, max_depth: usize) -> Vec<Vec<&str>> {
+        let mut depths = HashMap::<&str, usize>::new();
+        let mut stack = vec![(usr, 0)];
+        while let Some((usr, depth)) = stack.pop() {
+            if depth == max_depth {
+                continue;
+            }
+            let node = &self.nodes[usr];
+            for next in if up { &node.bases } else { &node.derived } {
+                if depths.get(next.as_str()).is_none_or(|d| *d < depth + 1) {
+                    depths.insert(next, depth + 1);
+                    stack.push((next, depth + 1));
+                }
+            }
+        }
+
+        let mut layers = vec![Vec::new(); depths.values().max().copied().unwrap_or(0)];
+        for (usr, depth) in depths {
+            layers[depth - 1].push(usr);
+        }
+        for layer in &mut layers {
+            layer.sort_by_key(|usr| self.nodes[*usr].full_name());
+        }
+        layers
+    }
+
+    /// Every class shown in the graph on a class's page. The page has to be
+    /// rebuilt when any of them changes
+    pub fn related(&self, class: &Entity) -> Vec<Entity<'e>> {
+        let Some(usr) = usr_of(class).filter(|usr| self.nodes.contains_key(usr)) else {
+            return Vec::new();
+        };
+        self.layers(&usr, true, usize::MAX)
+            .into_iter()
+            .chain(self.layers(&usr, false, DERIVED_DEPTH))
+            .flatten()
+            .map(|usr| self.nodes[usr].entity)
+            .collect()
+    }
+
+    /// A diagram of the bases and derived classes of a class as inline SVG,
+    /// or `None` if the class doesn't inherit from or get inherited by
+    /// anything
+    pub fn svg(&self, class: &Entity, builder: &Builder) -> Option<Html> {
+        let usr = usr_of(class)?;
+        let node = self.nodes.get(&usr)?;
+        if node.bases.is_empty() && node.derived.is_empty() {
+            return None;
+        }
+
+        let mut rows = self.layers(&usr, true, usize::MAX);
+        rows.reverse();
+        rows.push(vec![usr.as_str()]);
+        rows.extend(self.layers(&usr, false, DERIVED_DEPTH));
+
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let hidden = row.len().saturating_sub(MAX_ROW_LEN);
+                let mut items = row
+                    .into_iter()
+                    .take(MAX_ROW_LEN)
+                    .map(|usr| {
+                        let name = self.nodes[usr].entity.get_name();
+                        (Some(usr), name.unwrap_or("_anon".into()))
+                    })
+                    .collect::<Vec<_>>();
+                if hidden > 0 {
+                    items.push((None, format!("+{hidden} more")));
+                }
+                items
+            })
+            .collect::<Vec<_>>();
+
+        // Center every row horizontally
+        let box_width = |label: &str| label.chars().count() * CHAR_WIDTH + 2 * NODE_PADDING;
+        let row_width = |row: &[(Option<&str>, String)]| {
+            row.iter().map(|(_, label)| box_width(label)).sum::<usize>()
+                + H_GAP * row.len().saturating_sub(1)
+        };
+        let width = rows.iter().map(|row| row_width(row)).max().unwrap_or(0) + 2 * MARGIN;
+        let height = rows.len() * (NODE_HEIGHT + V_GAP) - V_GAP + 2 * MARGIN;
+
+        let mut boxes = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            let mut x = (width - row_width(row)) / 2;
+            for (usr, label) in row {
+                let box_width = box_width(label);
+                boxes.push(GraphBox {
+                    usr: *usr,
+                    label: label.clone(),
+                    x,
+                    y: MARGIN + i * (NODE_HEIGHT + V_GAP),
+                    width: box_width,
+                });
+                x += box_width + H_GAP;
+            }
+        }
+        let positions = boxes
+            .iter()
+            .filter_map(|b| Some((b.usr?, b)))
+            .collect::<HashMap<_, _>>();
+
+        // Arrows point from derived classes to their bases
+        let edges = boxes.iter().flat_map(|derived| {
+            derived
+                .usr
+                .map(|usr| self.nodes[usr].bases.as_slice())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|base| positions.get(base.as_str()))
+                .filter(|base| base.y < derived.y)
+                .map(|base| {
+                    HtmlElement::new("line")
+                        .with_attr("x1", derived.x + derived.width / 2)
+                        .with_attr("y1", derived.y)
+                        .with_attr("x2", base.x + base.width / 2)
+                        .with_attr("y2", base.y + NODE_HEIGHT)
+                        .with_attr("marker-end", "url(#inheritance-arrow)")
+                        .into()
+                })
+                .collect::<Vec<Html>>()
+        });
+
+        let hierarchy_url = HierarchyPage.url().to_absolute(builder.config.clone());
+        let nodes = boxes.iter().map(|b| {
+            let node = b.usr.map(|usr| &self.nodes[usr]);
+            let group = HtmlElement::new("g")
+                .with_class(match node {
+                    Some(_) if b.usr == Some(usr.as_str()) => "current",
+                    Some(node) if node.documented => "class",
+                    Some(_) => "external",
+                    None => "more",
+                })
+                .with_child_opt(node.map(|node| {
+                    HtmlElement::new("title").with_child(HtmlText::new(node.full_name()))
+                }))
+                .with_child(
+                    HtmlElement::new("rect")
+                        .with_attr("x", b.x)
+                        .with_attr("y", b.y)
+                        .with_attr("width", b.width)
+                        .with_attr("height", NODE_HEIGHT)
+                        .with_attr("rx", 4),
+                )
+                .with_child(
+                    HtmlElement::new("text")
+                        .with_attr("x", b.x + b.width / 2)
+                        .with_attr("y", b.y + NODE_HEIGHT / 2)
+                        .with_child(HtmlText::new(&b.label)),
+                );
+            // The current class is the page the graph is on
+            let link = match node {
+                Some(node) if node.documented && b.usr != Some(usr.as_str()) => {
+                    node.entity.abs_docs_url(builder.config.clone())
+                }
+                Some(_) => None,
+                None => Some(hierarchy_url.clone()),
+            };
+            match link {
+                Some(link) => HtmlElement::new("a")
+                    .with_attr("href", link)
+                    .with_child(group)
+                    .into(),
+                None => group.into(),
+            }
+        });
+
+        Some(
+            HtmlElement::new("svg")
+                .with_class("inheritance-graph")
+                .with_attr("xmlns", "http://www.w3.org/2000/svg")
+                .with_attr("viewBox", format!("0 0 {width} {height}"))
+                .with_attr("width", width)
+                .with_attr("style", "max-width: 100%; height: auto;")
+                .with_child(HtmlElement::new("style").with_child(Html::Raw(GRAPH_STYLE.into())))
+                .with_child(
+                    HtmlElement::new("defs").with_child(
+                        HtmlElement::new("marker")
+                            .with_attr("id", "inheritance-arrow")
+                            .with_attr("viewBox", "0 0 10 10")
+                            .with_attr("refX", 10)
+                            .with_attr("refY", 5)
+                            .with_attr("markerWidth", 8)
+                            .with_attr("markerHeight", 8)
+                            .with_attr("markerUnits", "userSpaceOnUse")
+                            .with_attr("orient", "auto")
+                            .with_child(
+                                HtmlElement::new("path").with_attr("d", "M 0 0 L 10 5 L 0 10 z"),
+                            ),
+                    ),
+                )
+                .with_children(edges.chain(nodes).collect())
+                .into(),
+        )
+    }
+
+    /// Documented classes that take part in inheritance but don't inherit
+    /// from any documented class, sorted by name
+    fn roots(&self) -> Vec<&str> {
+        let mut roots = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| {
+                node.documented
+                    && !(node.bases.is_empty() && node.derived.is_empty())
+                    && node.bases.iter().all(|base| !self.nodes[base].documented)
+            })
+            .map(|(usr, _)| usr.as_str())
+            .collect::<Vec<_>>();
+        roots.sort_by_key(|usr| self.nodes[*usr].full_name());
+        roots
+    }
+
+    /// Names of the bases of a class that aren't documented
+    fn external_bases(&self, usr: &str) -> Vec<String> {
+        self.nodes[usr]
+            .bases
+            .iter()
+            .map(|base| &self.nodes[base])
+            .filter(|base| !base.documented)
+            .map(|base| base.full_name())
+            .collect()
+    }
+
+    /// A class and everything derived from it as a nested list. Classes with
+    /// several bases are only expanded the first time they come up
+    fn fmt_tree<'a>(
+        &'a self,
+        usr: &'a str,
+        shown: &mut HashSet<&'a str>,
+        builder: &Builder,
+    ) -> Html {
+        let node = &self.nodes[usr];
+        let external = self.external_bases(usr);
+        let expand = shown.insert(usr);
+        let derived = node
+            .derived
+            .iter()
+            .filter(|derived| self.nodes[derived.as_str()].documented)
+            .collect::<Vec<_>>();
+        HtmlElement::new("li")
+            .with_child(
+                HtmlElement::new("a")
+                    .with_attr_opt("href", node.entity.abs_docs_url(builder.config.clone()))
+                    .with_child(HtmlElement::new("code").with_text(node.full_name())),
+            )
+            .with_child_opt((!external.is_empty()).then(|| {
+                Html::span(
+                    &["no-desc", "space-before"],
+                    &format!("inherits {}", external.join(", ")),
+                )
+            }))
+            .with_child_opt(
+                (!expand && !derived.is_empty())
+                    .then(|| Html::span(&["no-desc", "space-before"], "(listed above)")),
+            )
+            .with_child_opt((expand && !derived.is_empty()).then(|| {
+                HtmlElement::new("ul").with_children(
+                    derived
+                        .into_iter()
+                        .map(|derived| self.fmt_tree(derived, shown, builder))
+                        .collect(),
+                )
+            }))
+            .into()
+    }
+
+    fn markdown_tree<'a>(
+        &'a self,
+        usr: &'a str,
+        depth: usize,
+        shown: &mut HashSet<&'a str>,
+        page: &mut MarkdownPage,
+    ) {
+        let node = &self.nodes[usr];
+        let mut line = page.code_link(&node.entity, &node.full_name());
+        let external = self.external_bases(usr);
+        if !external.is_empty() {
+            line += &format!(" (inherits {})", external.join(", "));
+        }
+        let derived = node
+            .derived
+            .iter()
+            .filter(|derived| self.nodes[derived.as_str()].documented)
+            .collect::<Vec<_>>();
+        if !shown.insert(usr) {
+            if !derived.is_empty() {
+                line += " (listed above)";
+            }
+            page.nested_list_item(depth, &line);
+            return;
+        }
+        page.nested_list_item(depth, &line);
+        for derived in derived {
+            self.markdown_tree(derived, depth + 1, shown, page);
+        }
+    }
+}
+
+/// Page with every documented class that takes part in inheritance, with
+/// the classes derived from each one nested below it
+pub struct HierarchyPage;
+
+impl<'e> Entry<'e> for HierarchyPage {
+    fn name(&self) -> String {
+        "Class hierarchy".into()
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("hierarchy")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("git-merge", false)))
+    }
+}
+
+impl<'e> OutputEntry<'e> for HierarchyPage {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
+        let hierarchy = &builder.hierarchy;
+        let mut shown = HashSet::new();
+        let tree = hierarchy
+            .roots()
+            .into_iter()
+            .map(|root| hierarchy.fmt_tree(root, &mut shown, builder))
+            .collect::<Vec<_>>();
+        (
+            builder.config.templates.hierarchy.clone(),
+            vec![(
+                "hierarchy",
+                if tree.is_empty() {
+                    Html::span(&["no-desc"], "No classes inherit from each other")
+                } else {
+                    HtmlElement::new("ul")
+                        .with_class("hierarchy")
+                        .with_child(HtmlList::new(tree))
+                        .into()
+                },
+            )],
+        )
+    }
+
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        let hierarchy = &builder.hierarchy;
+        let mut page = MarkdownPage::new(builder, self.url());
+        page.heading(1, &self.name());
+        let mut shown = HashSet::new();
+        for root in hierarchy.roots() {
+            hierarchy.markdown_tree(root, 0, &mut shown, &mut page);
+        }
+        page.finish()
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "The inheritance hierarchy of the classes in {}",
+            builder.config.project.name
+        )
+    }
+}
@@ -0,0 +1,472 @@
+use super::{
+    builder::Builder,
+    cache::hash_of,
+    files::Root,
+    markdown_output::MarkdownPage,
+    shared::fmt_section,
+    traits::{BuildResult, Entry, NavItem, OutputEntry},
+};
+use crate::{
+    config::Config,
+    html::{Html, HtmlElement, HtmlText, template::Template},
+    url::UrlPath,
+};
+use clang::{Entity, EntityKind, token::TokenKind};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// How many headers the include report lists as the heaviest
+const HEAVIEST_COUNT: usize = 20;
+
+/// Paths from clang aren't always normalized, so they are canonicalized
+/// before being compared
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// A file included by another one
+pub struct Include {
+    pub path: PathBuf,
+    /// How the include is written, like `noahh/loader/Mod.hpp`
+    pub spelling: String,
+    /// Whether the path is in quotes rather than angle brackets
+    pub quoted: bool,
+}
+
+impl Include {
+    /// The directive the way it's written in the source
+    pub fn directive(&self) -> String {
+        if self.quoted {
+            format!("#include \"{}\"", self.spelling)
+        } else {
+            format!("#include <{}>", self.spelling)
+        }
+    }
+}
+
+/// What including a header pulls in along with it
+#[derive(Hash)]
+pub struct IncludeCost {
+    /// Number of other files it includes, directly or not
+    pub files: usize,
+    /// Combined size of those files
+    pub bytes: u64,
+}
+
+impl IncludeCost {
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} ({:.1} KB)",
+            self.files,
+            if self.files == 1 { "header" } else { "headers" },
+            self.bytes as f64 / 1024.0
+        )
+    }
+}
+
+/// A documented header
+struct FilePage {
+    url: UrlPath,
+    /// The path the header is included with
+    name: String,
+}
+
+/// Which files include which, from the `#include`s clang saw while parsing
+pub struct IncludeGraph {
+    includes: HashMap<PathBuf, Vec<Include>>,
+    included_by: HashMap<PathBuf, Vec<PathBuf>>,
+    pages: HashMap<PathBuf, FilePage>,
+}
+
+impl IncludeGraph {
+    pub fn new(unit: &Entity, roots: &[Root], config: &Config) -> Self {
+        let mut includes = HashMap::<PathBuf, Vec<Include>>::new();
+        let mut included_by = HashMap::<PathBuf, Vec<PathBuf>>::new();
+        for directive in unit.get_children() {
+            if directive.get_kind() != EntityKind::InclusionDirective {
+                continue;
+            }
+            let (Some(from), Some(to)) = (
+                directive
+                    .get_location()
+                    .and_then(|loc| loc.get_file_location().file),
+                directive.get_file(),
+            ) else {
+                continue;
+            };
+            let (from, to) = (canonical(&from.get_path()), canonical(&to.get_path()));
+            let list = includes.entry(from.clone()).or_default();
+            if list.iter().any(|include| include.path == to) {
+                continue;
+            }
+            // The path is a single string literal token when it's in quotes
+            let quoted = directive.get_range().is_some_and(|range| {
+                range
+                    .tokenize()
+                    .iter()
+                    .any(|token| token.get_kind() == TokenKind::Literal)
+            });
+            list.push(Include {
+                path: to.clone(),
+                spelling: directive.get_name().unwrap_or_default(),
+                quoted,
+            });
+            included_by.entry(to).or_default().push(from);
+        }
+
+        let pages = roots
+            .iter()
+            .flat_map(|root| root.dir.all_files())
+            .map(|file| {
+                (
+                    canonical(&file.abs_path(config)),
+                    FilePage {
+                        url: file.url(),
+                        name: file.include_path(),
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut graph = Self {
+            includes,
+            included_by,
+            pages,
+        };
+        for includers in graph.included_by.values_mut() {
+            includers
+                .sort_by_cached_key(|path| graph.pages.get(path).map(|page| page.name.clone()));
+        }
+        graph
+    }
+
+    /// Name of a file for listing it. Documented headers go by the path
+    /// they're included with and everything else by its full path
+    pub fn name(&self, path: &Path) -> String {
+        self.pages
+            .get(&canonical(path))
+            .map(|page| page.name.clone())
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    }
+
+    /// Files included directly by a file
+    pub fn includes(&self, path: &Path) -> &[Include] {
+        self.includes
+            .get(&canonical(path))
+            .map(|list| list.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Documented headers that directly include a file, sorted by name
+    pub fn included_by(&self, path: &Path) -> Vec<&Path> {
+        self.included_by
+            .get(&canonical(path))
+            .map(|list| {
+                list.iter()
+                    .filter(|path| self.pages.contains_key(*path))
+                    .map(|path| path.as_path())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Every file a file includes, directly or through other headers
+    pub fn cost(&self, path: &Path) -> IncludeCost {
+        let start = canonical(path);
+        let mut seen = HashSet::from([start.as_path()]);
+        let mut stack = vec![start.as_path()];
+        while let Some(path) = stack.pop() {
+            for include in self.includes.get(path).into_iter().flatten() {
+                if seen.insert(&include.path) {
+                    stack.push(&include.path);
+                }
+            }
+        }
+        seen.remove(start.as_path());
+        IncludeCost {
+            files: seen.len(),
+            bytes: seen
+                .iter()
+                .filter_map(|path| fs::metadata(path).ok())
+                .map(|meta| meta.len())
+                .sum(),
+        }
+    }
+
+    /// Hash of what the include sections on a file's page show, since those
+    /// change when other headers do
+    pub fn key(&self, path: &Path) -> u64 {
+        hash_of(&(
+            self.includes(path)
+                .iter()
+                .map(|include| (&include.path, include.directive()))
+                .collect::<Vec<_>>(),
+            self.included_by(path),
+            self.cost(path),
+        ))
+    }
+
+    /// Groups of files that include each other in a loop. Only loops that
+    /// go through at least one documented header are listed
+    pub fn cycles(&self) -> Vec<Vec<&Path>> {
+        // Tarjan's algorithm for strongly connected components
+        struct State<'a> {
+            index: HashMap<&'a Path, usize>,
+            low: HashMap<&'a Path, usize>,
+            stack: Vec<&'a Path>,
+            on_stack: HashSet<&'a Path>,
+            components: Vec<Vec<&'a Path>>,
+        }
+
+        fn visit<'a>(graph: &'a IncludeGraph, path: &'a Path, state: &mut State<'a>) {
+            let index = state.index.len();
+            state.index.insert(path, index);
+            state.low.insert(path, index);
+            state.stack.push(path);
+            state.on_stack.insert(path);
+
+            for include in graph.includes.get(path).into_iter().flatten() {
+                let next = include.path.as_path();
+                if !state.index.contains_key(next) {
+                    visit(graph, next, state);
+                    let low = state.low[path].min(state.low[next]);
+                    state.low.insert(path, low);
+                } else if state.on_stack.contains(next) {
+                    let low = state.low[path].min(state.index[next]);
+                    state.low.insert(path, low);
+                }
+            }
+
+            if state.low[path] == state.index[path] {
+                let mut component = Vec::new();
+                while let Some(member) = state.stack.pop() {
+                    state.on_stack.remove(member);
+                    component.push(member);
+                    if member == path {
+                        break;
+                    }
+                }
+                state.components.push(component);
+            }
+        }
+
+        let mut state = State {
+            index: HashMap::new(),
+            low: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            components: Vec::new(),
+        };
+        let mut files = self.includes.keys().collect::<Vec<_>>();
+        files.sort();
+        for path in files {
+            if !state.index.contains_key(path.as_path()) {
+                visit(self, path, &mut state);
+            }
+        }
+
+        let mut cycles = state
+            .components
+            .into_iter()
+            .filter(|component| {
+                let includes_itself = || {
+                    self.includes(component[0])
+                        .iter()
+                        .any(|include| include.path == component[0])
+                };
+                (component.len() > 1 || includes_itself())
+                    && component.iter().any(|path| self.pages.contains_key(*path))
+            })
+            .map(|mut component| {
+                component.sort_by_key(|path| self.name(path));
+                component
+            })
+            .collect::<Vec<_>>();
+        cycles.sort_by_key(|component| self.name(component[0]));
+        cycles
+    }
+
+    /// The documented headers that pull in the most other files
+    pub fn heaviest(&self) -> Vec<(&Path, IncludeCost)> {
+        let mut costs = self
+            .pages
+            .keys()
+            .map(|path| (path.as_path(), self.cost(path)))
+            .filter(|(_, cost)| cost.files > 0)
+            .collect::<Vec<_>>();
+        costs.sort_by(|(a, a_cost), (b, b_cost)| {
+            b_cost
+                .files
+                .cmp(&a_cost.files)
+                .then(b_cost.bytes.cmp(&a_cost.bytes))
+                .then_with(|| self.name(a).cmp(&self.name(b)))
+        });
+        costs.truncate(HEAVIEST_COUNT);
+        costs
+    }
+
+    /// A link to the page of a file, or just its name if it isn't documented
+    pub fn fmt_file(&self, path: &Path, text: &str, builder: &Builder) -> Html {
+        let code = HtmlElement::new("code").with_text(text);
+        match self.pages.get(&canonical(path)) {
+            Some(page) => HtmlElement::new("a")
+                .with_attr("href", page.url.to_absolute(builder.config.clone()))
+                .with_child(code)
+                .into(),
+            None => code.into(),
+        }
+    }
+
+    /// The same for markdown pages
+    pub fn markdown_file(&self, path: &Path, text: &str, page: &MarkdownPage) -> String {
+        match self.pages.get(&canonical(path)) {
+            Some(file) => format!("[`{text}`]({})", page.link(&file.url)),
+            None => format!("`{text}`"),
+        }
+    }
+
+    /// Summary of what including a header costs, for its page
+    pub fn cost_summary(&self, path: &Path) -> Option<String> {
+        let cost = self.cost(path);
+        (cost.files != 0).then(|| format!("Including this header pulls in {}", cost.describe()))
+    }
+
+    pub fn fmt_cost(&self, path: &Path) -> Html {
+        match self.cost_summary(path) {
+            Some(summary) => HtmlElement::new("p")
+                .with_child(HtmlText::new(summary))
+                .into(),
+            None => Html::p(""),
+        }
+    }
+
+    pub fn fmt_includes(&self, path: &Path, builder: &Builder) -> Html {
+        fmt_section(
+            "Includes",
+            self.includes(path)
+                .iter()
+                .map(|include| {
+                    HtmlElement::new("div")
+                        .with_class("entity")
+                        .with_child(self.fmt_file(&include.path, &include.directive(), builder))
+                        .into()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn fmt_included_by(&self, path: &Path, builder: &Builder) -> Html {
+        fmt_section(
+            "Included by",
+            self.included_by(path)
+                .into_iter()
+                .map(|includer| {
+                    HtmlElement::new("div")
+                        .with_class("entity")
+                        .with_child(self.fmt_file(includer, &self.name(includer), builder))
+                        .into()
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Page listing include cycles and the headers that are the most expensive
+/// to include
+pub struct IncludeReport;
+
+impl<'e> Entry<'e> for IncludeReport {
+    fn name(&self) -> String {
+        "Include report".into()
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("includes")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("layers", false)))
+    }
+}
+
+impl<'e> OutputEntry<'e> for IncludeReport {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
+        let graph = &builder.includes;
+        let cycles = graph
+            .cycles()
+            .into_iter()
+            .map(|cycle| {
+                let mut files = cycle
+                    .iter()
+                    .map(|path| graph.fmt_file(path, &graph.name(path), builder))
+                    .collect::<Vec<_>>();
+                // Close the loop
+                files.push(graph.fmt_file(cycle[0], &graph.name(cycle[0]), builder));
+                let mut line = HtmlElement::new("div").with_class("entity");
+                for (i, file) in files.into_iter().enumerate() {
+                    if i > 0 {
+                        line.add_child(Html::span(&["space-before", "space-after"], "→"));
+                    }
+                    line.add_child(file);
+                }
+                line.into()
+            })
+            .collect();
+        let heaviest = graph
+            .heaviest()
+            .into_iter()
+            .map(|(path, cost)| {
+                HtmlElement::new("div")
+                    .with_class("entity")
+                    .with_child(graph.fmt_file(path, &graph.name(path), builder))
+                    .with_child(Html::span(&["no-desc", "space-before"], &cost.describe()))
+                    .into()
+            })
+            .collect();
+        (
+            builder.config.templates.includes.clone(),
+            vec![
+                ("cycles", fmt_section("Include cycles", cycles)),
+                ("heaviest", fmt_section("Heaviest headers", heaviest)),
+            ],
+        )
+    }
+
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        let graph = &builder.includes;
+        let mut page = MarkdownPage::new(builder, self.url());
+        page.heading(1, &self.name());
+        page.section("Include cycles", graph.cycles(), |page, cycle| {
+            let files = cycle
+                .iter()
+                .chain([&cycle[0]])
+                .map(|path| graph.markdown_file(path, &graph.name(path), page))
+                .collect::<Vec<_>>();
+            page.list_item(&files.join(" → "));
+        });
+        page.section(
+            "Heaviest headers",
+            graph.heaviest(),
+            |page, (path, cost)| {
+                let file = graph.markdown_file(path, &graph.name(path), page);
+                page.list_item(&format!("{file} - {}", cost.describe()));
+            },
+        );
+        page.finish()
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Include cycles and the most expensive headers to include in {}",
+            builder.config.project.name
+        )
+    }
+}
@@ -0,0 +1,212 @@
+use super::{builder::Builder, traits::EntityMethods};
+use crate::versions::find_pages;
+use percent_encoding::percent_decode_str;
+use regex_lite::Regex;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+/// Undo the escaping minify-html and the HTML generator do in attributes
+fn unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn decode(text: &str) -> String {
+    percent_decode_str(text).decode_utf8_lossy().to_string()
+}
+
+/// Values of the attribute `re` matches. Minified HTML leaves out the quotes
+/// around them when it can
+fn attr_values<'a>(re: &'a Regex, html: &'a str) -> impl Iterator<Item = String> + 'a {
+    re.captures_iter(html).filter_map(|caps| {
+        caps.get(1)
+            .or(caps.get(2))
+            .or(caps.get(3))
+            .map(|m| unescape(m.as_str()))
+    })
+}
+
+/// Resolve a link on a page to a path in the output directory and a fragment.
+/// Returns `None` for links that lead outside the docs
+fn resolve(href: &str, page: &str, root: &str) -> Option<(String, Option<String>)> {
+    let scheme = href.find(':').is_some_and(|i| !href[..i].contains('/'));
+    if href.is_empty() || scheme || href.starts_with("//") {
+        return None;
+    }
+    let (path, fragment) = match href.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment.to_owned())),
+        None => (href, None),
+    };
+    let path = path.split(['?']).next().unwrap_or(path);
+
+    let full = if path.is_empty() {
+        page.to_owned()
+    } else if let Some(abs) = path.strip_prefix('/') {
+        // Absolute links have to be under the docs root to be checkable
+        if root.is_empty() {
+            abs.to_owned()
+        } else if abs == root {
+            String::new()
+        } else {
+            abs.strip_prefix(root)?.strip_prefix('/')?.to_owned()
+        }
+    } else {
+        format!("{page}/{path}")
+    };
+
+    let mut parts: Vec<&str> = Vec::new();
+    for part in full.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some((parts.join("/"), fragment.filter(|f| !f.is_empty())))
+}
+
+struct Page {
+    /// Every `id` on the page
+    ids: HashSet<String>,
+    /// Links on the page with the text of the link
+    links: Vec<(String, String)>,
+}
+
+struct LinkChecker<'a> {
+    output_dir: &'a Path,
+    link_re: Regex,
+    id_re: Regex,
+    tag_re: Regex,
+    ids: HashMap<String, HashSet<String>>,
+}
+
+impl LinkChecker<'_> {
+    fn read_page(&self, page: &str) -> Page {
+        let mut res = Page {
+            ids: HashSet::new(),
+            links: Vec::new(),
+        };
+        for file in ["index.html", "content.html"] {
+            let Ok(html) = fs::read_to_string(self.output_dir.join(page).join(file)) else {
+                continue;
+            };
+            res.ids.extend(attr_values(&self.id_re, &html));
+            for caps in self.link_re.captures_iter(&html) {
+                let Some(href) = caps.get(1).or(caps.get(2)).or(caps.get(3)) else {
+                    continue;
+                };
+                let text = self.tag_re.replace_all(&caps[4], "");
+                let link = (unescape(href.as_str()), unescape(text.trim()));
+                // content.html is part of index.html
+                if !res.links.contains(&link) {
+                    res.links.push(link);
+                }
+            }
+        }
+        res
+    }
+
+    /// Find the page directory a path refers to, if it exists
+    fn find_target(&self, path: &str) -> Option<String> {
+        [path.to_owned(), decode(path)]
+            .into_iter()
+            .find(|p| self.output_dir.join(p).join("index.html").is_file())
+    }
+
+    fn is_file(&self, path: &str) -> bool {
+        let dir = self.output_dir;
+        dir.join(path).is_file() || dir.join(decode(path)).is_file()
+    }
+
+    fn has_id(&mut self, page: &str, id: &str) -> bool {
+        if !self.ids.contains_key(page) {
+            let ids = self.read_page(page).ids;
+            self.ids.insert(page.to_owned(), ids);
+        }
+        let ids = &self.ids[page];
+        let id = decode(id);
+        // Overloads have anchors like `name-int-float`, and the page scripts
+        // send links to `#name` to the first one
+        ids.contains(&id) || ids.iter().any(|i| i.starts_with(&format!("{id}-")))
+    }
+}
+
+/// Checks that the internal links in the generated pages lead to pages and
+/// elements that exist. Returns a list of problems, each naming the page the
+/// link is on and what it documents
+pub fn check_links<'e>(builder: &'e Builder<'e>) -> Vec<String> {
+    let config = &builder.config;
+    let root = config
+        .output_url
+        .as_ref()
+        .map(|url| url.url_safe_parts().join("/"))
+        .unwrap_or_default();
+
+    // Name the entity a page is for so problems are easier to track down
+    let entities = builder
+        .root
+        .get(&|_| true)
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.url().url_safe_parts().join("/"),
+                entry.entity().full_name().join("::"),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut checker = LinkChecker {
+        output_dir: &config.output_dir,
+        link_re: Regex::new(
+            r#"(?s)<a\s[^>]*?\bhref=(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))[^>]*>(.*?)</a>"#,
+        )
+        .unwrap(),
+        id_re: Regex::new(r#"\sid=(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap(),
+        tag_re: Regex::new(r"<[^>]*>").unwrap(),
+        ids: HashMap::new(),
+    };
+
+    let mut pages = Vec::new();
+    find_pages(&config.output_dir, "", &mut pages);
+    pages.sort();
+
+    let mut problems = Vec::new();
+    for page in pages {
+        let contents = checker.read_page(&page);
+        checker.ids.insert(page.clone(), contents.ids);
+
+        let source = match entities.get(&page) {
+            Some(entity) => format!("/{page} (for {entity})"),
+            None => format!("/{page}"),
+        };
+        for (href, text) in contents.links {
+            let Some((path, fragment)) = resolve(&href, &page, &root) else {
+                continue;
+            };
+            let problem = match checker.find_target(&path) {
+                Some(target) => fragment
+                    .filter(|id| !checker.has_id(&target, id))
+                    .map(|id| format!("there is no element with the id `{id}`")),
+                None if fragment.is_none() && checker.is_file(&path) => None,
+                None => Some("there is no page there".into()),
+            };
+            if let Some(problem) = problem {
+                let text = if text.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (`{text}`)")
+                };
+                problems.push(format!("{source}: Broken link to {href}{text}, {problem}"));
+            }
+        }
+    }
+    problems
+}
@@ -0,0 +1,159 @@
+use super::{
+    builder::Builder,
+    comment::JSDocComment,
+    namespace::{CppItem, Namespace},
+    shared::get_macro_params_and_body,
+    traits::{ASTEntry, Access, EntityMethods, Include},
+};
+use clang::{Entity, EntityKind, TypeKind};
+
+/// Checks that the `@param`, `@tparam` and `@returns` commands in doc
+/// comments actually match the entities they are documenting, and that
+/// `@see` commands refer to something that exists. Returns a list of
+/// problems, each prefixed with the location of the entity
+pub fn lint_docs<'e>(builder: &'e Builder<'e>) -> Vec<String> {
+    let mut problems = Vec::new();
+    lint_namespace(&builder.root, builder, &mut problems);
+    problems
+}
+
+fn lint_namespace<'e>(
+    namespace: &'e Namespace<'e>,
+    builder: &'e Builder<'e>,
+    out: &mut Vec<String>,
+) {
+    for entry in namespace.entries.values() {
+        match entry {
+            CppItem::Namespace(ns) => lint_namespace(ns, builder, out),
+            CppItem::Class(_) | CppItem::Struct(_) => {
+                lint_entity(entry.entity(), builder, out);
+                for fun in entry
+                    .entity()
+                    .get_member_functions(Access::All, Include::All)
+                {
+                    lint_entity(&fun, builder, out);
+                }
+                lint_members(entry.entity(), builder, out);
+            }
+            _ => {
+                for entity in entry.entities() {
+                    lint_entity(&entity, builder, out);
+                    lint_members(&entity, builder, out);
+                }
+            }
+        }
+    }
+}
+
+/// Lint the fields, static members and enumerators of an entity, which have
+/// doc comments of their own
+fn lint_members<'e>(entity: &Entity<'e>, builder: &'e Builder<'e>, out: &mut Vec<String>) {
+    for child in entity.get_children() {
+        if matches!(
+            child.get_kind(),
+            EntityKind::FieldDecl | EntityKind::VarDecl | EntityKind::EnumConstantDecl
+        ) {
+            lint_entity(&child, builder, out);
+        }
+    }
+}
+
+fn lint_entity<'e>(entity: &Entity<'e>, builder: &'e Builder<'e>, out: &mut Vec<String>) {
+    // Only lint our own code
+    if entity.config_source(builder.config.clone()).is_none() {
+        return;
+    }
+    // Clang only finds the comments of macros through `get_doc_comment`.
+    // Other entities use their own comment, since the one a method without
+    // docs inherits from the method it overrides is linted there
+    let comment = if entity.get_kind() == EntityKind::MacroDefinition {
+        entity.get_doc_comment()
+    } else {
+        entity.get_comment()
+    };
+    let Some(comment) = comment else {
+        return;
+    };
+    let comment = JSDocComment::parse(comment, builder);
+
+    let name = entity.full_name().join("::");
+    let location = entity
+        .get_location()
+        .map(|loc| loc.get_file_location())
+        .map(|loc| {
+            format!(
+                "{}:{}",
+                loc.file
+                    .map(|f| f.get_path().to_string_lossy().to_string())
+                    .unwrap_or("<unknown>".into()),
+                loc.line
+            )
+        })
+        .unwrap_or("<unknown>".into());
+    let mut problem = |msg: String| out.push(format!("{location}: {msg}"));
+
+    let arg_names = if entity.get_kind() == EntityKind::MacroDefinition {
+        get_macro_params_and_body(entity).and_then(|(params, _)| params)
+    } else {
+        entity
+            .get_function_arguments()
+            .or_else(|| entity.get_arguments())
+            .map(|args| args.iter().filter_map(|arg| arg.get_name()).collect())
+    };
+    if let Some(arg_names) = arg_names {
+        for (param, _) in comment.params() {
+            if !arg_names.contains(param) {
+                problem(format!(
+                    "Documented parameter `{param}` does not exist on `{name}`"
+                ));
+            }
+        }
+        // Only complain about missing ones if some parameters are documented
+        if !comment.params().is_empty() {
+            for arg in &arg_names {
+                if !comment.params().iter().any(|(param, _)| param == arg) {
+                    problem(format!("Parameter `{arg}` of `{name}` is not documented"));
+                }
+            }
+        }
+    }
+
+    let tparam_names = entity
+        .get_children()
+        .iter()
+        .filter(|child| {
+            matches!(
+                child.get_kind(),
+                EntityKind::TemplateTypeParameter
+                    | EntityKind::NonTypeTemplateParameter
+                    | EntityKind::TemplateTemplateParameter
+            )
+        })
+        .filter_map(|child| child.get_name())
+        .collect::<Vec<_>>();
+    for (tparam, _) in comment.tparams() {
+        if !tparam_names.contains(tparam) {
+            problem(format!(
+                "Documented template parameter `{tparam}` does not exist on `{name}`"
+            ));
+        }
+    }
+
+    if comment.returns().is_some()
+        && entity
+            .get_result_type()
+            .is_some_and(|ty| ty.get_kind() == TypeKind::Void)
+    {
+        problem(format!(
+            "`{name}` returns void but documents a return value"
+        ));
+    }
+
+    for target in comment.see_targets() {
+        if builder.root.find_by_name(target).is_none() {
+            problem(format!(
+                "Unable to resolve @see target `{target}` in the docs of `{name}`"
+            ));
+        }
+    }
+}
@@ -44,7 +44,7 @@ impl Metadata {
     }
 }
 
-fn parse_markdown_metadata(doc: &str) -> (&str, Option<Metadata>) {
+pub fn parse_markdown_metadata(doc: &str) -> (&str, Option<Metadata>) {
     // if the document has no metadata just parse it as markdown
     if !doc.trim_start().starts_with("---") {
         return (doc, None);
@@ -79,6 +79,7 @@ struct MDStream<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlP
     metadata: Option<Metadata>,
     insert_para_stage: InsertP,
     inside_code_block: bool,
+    heading_ids: std::vec::IntoIter<String>,
 }
 
 impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>>
@@ -89,6 +90,7 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>>
         url_fixer: Option<F>,
         builder: &'b Builder<'e>,
         metadata: Option<Metadata>,
+        heading_ids: Vec<String>,
     ) -> MDStream<'i, 'c, 'b, 'e, SIZE, F> {
         MDStream {
             iter: iter.lookahead_cached::<SIZE>(),
@@ -97,6 +99,7 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>>
             metadata,
             insert_para_stage: InsertP::Dont,
             inside_code_block: false,
+            heading_ids: heading_ids.into_iter(),
         }
     }
 }
@@ -168,32 +171,10 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>> Itera
                 }
                 // Add id to heading so they can be navigated to with url#header
                 Tag::Heading(lvl, mut frag, mut classes) => {
-                    if frag.is_none() && (lvl as usize) < 4 {
-                        let mut buf = String::new();
-                        for t in self.iter.lookahead() {
-                            match t {
-                                Some(Event::Text(t)) => {
-                                    if !buf.is_empty() {
-                                        buf += " ";
-                                    }
-                                    // all text must be lowercase
-                                    buf += &t
-                                        .to_string()
-                                        .chars()
-                                        // no punctuation
-                                        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
-                                        .collect::<String>()
-                                        .to_lowercase();
-                                }
-                                Some(Event::End(Tag::Heading(_, _, _))) => break,
-                                // non-text is removed
-                                _ => {}
-                            }
-                        }
-                        // replace spaces with single hyphens
-                        buf = buf.split_whitespace().collect::<Vec<_>>().join("-");
-
-                        frag = Some(CowStr::Boxed(Box::from(buf)));
+                    if (lvl as usize) < 4
+                        && let Some(id) = self.heading_ids.next()
+                    {
+                        frag = Some(CowStr::Boxed(Box::from(id)));
                     }
                     if let Some(ref meta) = self.metadata
                         && meta.style == Style::QnA
@@ -249,6 +230,7 @@ pub fn fmt_markdown<F: Fn(UrlPath) -> Option<UrlPath>>(
         url_fixer,
         builder,
         meta,
+        heading_ids(text),
     );
 
     let mut content = String::new();
@@ -260,6 +242,84 @@ pub fn fmt_markdown<F: Fn(UrlPath) -> Option<UrlPath>>(
         .into()
 }
 
+/// Anchor ids of the headings that get one (levels 1 to 3), in order. Uses
+/// the id given with `{#id}` if there is one and otherwise makes one from
+/// the text of the heading, with code and other non-text left out
+fn heading_ids(text: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut heading: Option<String> = None;
+    for event in pulldown_cmark::Parser::new_ext(text, pulldown_cmark::Options::all()) {
+        match event {
+            Event::Start(Tag::Heading(lvl, frag, _)) if (lvl as usize) < 4 => match frag {
+                Some(frag) => ids.push(frag.to_string()),
+                None => heading = Some(String::new()),
+            },
+            Event::End(Tag::Heading(_, _, _)) => {
+                if let Some(heading) = heading.take() {
+                    // replace spaces with single hyphens
+                    ids.push(heading.split_whitespace().collect::<Vec<_>>().join("-"));
+                }
+            }
+            Event::Text(t) => {
+                if let Some(ref mut heading) = heading {
+                    heading.push(' ');
+                    // all text must be lowercase with no punctuation
+                    heading.extend(
+                        t.chars()
+                            .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+                            .flat_map(char::to_lowercase),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    ids
+}
+
+/// Split a markdown document into its sections as plain text. Each section
+/// starts with a heading (with the same anchor id the rendered page uses),
+/// except for the text before the first heading
+pub fn extract_sections_from_md(text: &str) -> Vec<(Option<(String, String)>, String)> {
+    let (text, _) = parse_markdown_metadata(text);
+
+    let mut ids = heading_ids(text).into_iter();
+    let mut sections = vec![(None, String::new())];
+    let mut heading: Option<String> = None;
+    for event in pulldown_cmark::Parser::new_ext(text, pulldown_cmark::Options::all()) {
+        match event {
+            Event::Start(Tag::Heading(lvl, _, _)) if (lvl as usize) < 4 => {
+                heading = Some(String::new());
+            }
+            Event::End(Tag::Heading(lvl, _, _)) if (lvl as usize) < 4 => {
+                let title = heading.take().unwrap_or_default();
+                let id = ids.next().unwrap_or_default();
+                sections.push((Some((title.trim().to_owned(), id)), String::new()));
+            }
+            Event::Text(t) | Event::Code(t) => {
+                let target = match heading {
+                    Some(ref mut heading) => heading,
+                    None => &mut sections.last_mut().unwrap().1,
+                };
+                if !target.is_empty() {
+                    target.push(' ');
+                }
+                target.push_str(&t);
+            }
+            _ => {}
+        }
+    }
+
+    sections
+        .into_iter()
+        .map(|(heading, text)| {
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (heading, text)
+        })
+        .filter(|(heading, text)| heading.is_some() || !text.is_empty())
+        .collect()
+}
+
 #[allow(clippy::ptr_arg)]
 pub fn extract_metadata_from_md(text: &String, default_title: Option<String>) -> Option<Metadata> {
     let (text, metadata) = parse_markdown_metadata(text);
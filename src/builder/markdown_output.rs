@@ -0,0 +1,594 @@
+use super::{
+    builder::Builder,
+    comment::JSDocComment,
+    function::Function,
+    markdown::parse_markdown_metadata,
+    namespace::Namespace,
+    shared::{
+        InheritedMembers, fmt_autolinks_with, get_fields, get_inherited_members, member_fun_link,
+    },
+    signature::{
+        alias_decl, classlike_decl, enum_decl, enumerator_decl, fun_signature, macro_decl,
+        variable_decl,
+    },
+    traits::{ASTEntry, Access, EntityMethods, Entry, Include},
+};
+use crate::url::{URL_RESERVED, UrlPath};
+use clang::{Accessibility, Entity, EntityKind};
+use percent_encoding::utf8_percent_encode;
+use pulldown_cmark::{Event, LinkType, Tag};
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// Where the markdown page for a URL is written, relative to the output
+/// directory
+pub fn markdown_path(url: &UrlPath) -> PathBuf {
+    if url.is_empty() {
+        PathBuf::from("index.md")
+    } else {
+        PathBuf::from(format!("{}.md", url.url_safe_parts().join("/")))
+    }
+}
+
+/// Path from the directory of `from` to `target`, both relative to the
+/// output directory
+fn relative_path(from: &[String], target: &[String]) -> String {
+    let dir = &from[..from.len().saturating_sub(1)];
+    let common = dir.iter().zip(target).take_while(|(a, b)| a == b).count();
+    std::iter::repeat_n("..".to_owned(), dir.len() - common)
+        .chain(target[common..].iter().cloned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn path_parts(path: &Path) -> Vec<String> {
+    path.to_string_lossy()
+        .split(['/', '\\'])
+        .map(|s| s.to_owned())
+        .collect()
+}
+
+/// Wrap code in a fence longer than any run of backticks inside it
+fn fenced(code: &str, lang: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in code.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}\n\n", code.trim_matches('\n'))
+}
+
+/// Make text fit in a single table cell
+fn table_cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// Builds the markdown for a single page. Links between pages are relative so
+/// the output works wherever it ends up being hosted
+pub struct MarkdownPage<'e> {
+    builder: &'e Builder<'e>,
+    url: UrlPath,
+    content: String,
+}
+
+impl<'e> MarkdownPage<'e> {
+    pub fn new(builder: &'e Builder<'e>, url: UrlPath) -> Self {
+        Self {
+            builder,
+            url,
+            content: String::new(),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.content.trim_end().to_owned() + "\n"
+    }
+
+    pub fn push(&mut self, text: &str) {
+        // Blocks after a list need a blank line to not be part of it
+        if self.content.ends_with('\n') && !self.content.ends_with("\n\n") {
+            self.content.push('\n');
+        }
+        self.content += text.trim_end();
+        self.content += "\n\n";
+    }
+
+    pub fn list_item(&mut self, text: &str) {
+        self.nested_list_item(0, text);
+    }
+
+    pub fn nested_list_item(&mut self, depth: usize, text: &str) {
+        writeln!(self.content, "{}- {}", "  ".repeat(depth), text.trim()).unwrap();
+    }
+
+    pub fn heading(&mut self, level: usize, text: &str) {
+        self.push(&format!("{} {text}", "#".repeat(level)));
+    }
+
+    pub fn code(&mut self, code: &str) {
+        self.content += &fenced(code, "cpp");
+    }
+
+    /// Relative link to another page
+    pub fn link(&self, to: &UrlPath) -> String {
+        let mut link = relative_path(
+            &path_parts(&markdown_path(&self.url)),
+            &path_parts(&markdown_path(to)),
+        );
+        if let Some(fragment) = to.fragment() {
+            write!(link, "#{}", utf8_percent_encode(fragment, URL_RESERVED)).unwrap();
+        }
+        link
+    }
+
+    pub fn entity_link(&self, entity: &Entity) -> Option<String> {
+        let url = entity.abs_docs_url(self.builder.config.clone())?;
+        // Standard library items link to cppreference
+        if entity.full_name().first().is_some_and(|n| n == "std") {
+            return Some(format!("https://{}", url.to_raw_string()));
+        }
+        let root = self.builder.config.output_url.clone();
+        Some(self.link(&url.strip_prefix(root.unwrap_or_else(UrlPath::new))))
+    }
+
+    /// Rewrite links to `/some/page` that are relative to the docs root to
+    /// be relative to this page instead
+    pub fn fix_links(&self, text: &str) -> String {
+        let mut replacements = Vec::new();
+        for (event, range) in
+            pulldown_cmark::Parser::new_ext(text, pulldown_cmark::Options::all()).into_offset_iter()
+        {
+            let (Event::Start(Tag::Link(LinkType::Inline, dest, _))
+            | Event::Start(Tag::Image(LinkType::Inline, dest, _))) = event
+            else {
+                continue;
+            };
+            if !dest.starts_with('/') || dest.starts_with("//") {
+                continue;
+            }
+            let Some(pos) = text[range.clone()].rfind(dest.as_ref()) else {
+                continue;
+            };
+
+            let (path, fragment) = match dest.split_once('#') {
+                Some((path, fragment)) => (path, Some(fragment)),
+                None => (dest.as_ref(), None),
+            };
+            let Ok(url) = UrlPath::parse(path) else {
+                continue;
+            };
+            // Anything with an extension is an asset and not a page
+            let is_asset = url
+                .raw_file_name()
+                .is_some_and(|name| name.contains('.') && !name.ends_with(".md"));
+            let mut fixed = if is_asset {
+                relative_path(
+                    &path_parts(&markdown_path(&self.url)),
+                    &url.url_safe_parts(),
+                )
+            } else {
+                self.link(&url.remove_extension(".md"))
+            };
+            if let Some(fragment) = fragment {
+                write!(fixed, "#{fragment}").unwrap();
+            }
+            replacements.push((range.start + pos..range.start + pos + dest.len(), fixed));
+        }
+
+        let mut res = text.to_owned();
+        for (range, fixed) in replacements.into_iter().rev() {
+            res.replace_range(range, &fixed);
+        }
+        res
+    }
+
+    /// Prepare markdown from a doc comment or tutorial for this page
+    pub fn text(&self, text: &str) -> String {
+        self.fix_links(&fmt_autolinks_with(self.builder, text, &|entity| {
+            self.entity_link(entity)
+        }))
+    }
+
+    /// Title of the page with the include for the entity
+    pub fn title<T: ASTEntry<'e>>(&mut self, kind: &str, entry: &T) {
+        self.heading(1, &format!("{kind} `{}`", entry.name()));
+        let config = self.builder.config.clone();
+        if let Some(path) = entry.entity().include_path(config.clone()) {
+            let include = format!("`#include <{}>`", path.to_raw_string());
+            let online = entry
+                .entity()
+                .config_source(config.clone())
+                .is_none_or(|s| s.exists_online);
+            match entry.entity().github_url(config).filter(|_| online) {
+                Some(link) => self.push(&format!("[{include}]({link})")),
+                None => self.push(&include),
+            }
+        }
+    }
+
+    fn see(&self, see: &str) -> String {
+        // Anything that looks like a link is used as-is
+        let see = see.trim();
+        if see.starts_with('[') || see.starts_with('<') {
+            return self.fix_links(see);
+        }
+        let (target, desc) = see.split_once(char::is_whitespace).unwrap_or((see, ""));
+        let link = if target.contains("://") {
+            Some(target.to_owned())
+        } else {
+            self.builder
+                .root
+                .find_by_name(target)
+                .and_then(|entity| self.entity_link(&entity))
+        };
+        let target = match link {
+            Some(link) => format!("[`{target}`]({link})"),
+            None => format!("`{target}`"),
+        };
+        format!("{target} {}", desc.trim()).trim_end().to_owned()
+    }
+
+    /// The contents of a doc comment
+    pub fn doc(&mut self, comment: Option<String>, include_examples: bool) {
+        let Some(comment) = comment else {
+            self.push("*No description provided*");
+            return;
+        };
+        let doc = JSDocComment::parse(comment, self.builder).export();
+
+        let tags = doc
+            .version
+            .iter()
+            .map(|v| format!("Version {v}"))
+            .chain(doc.since.iter().map(|v| format!("Since {v}")))
+            .chain(doc.short_notes.iter().cloned())
+            .map(|tag| format!("`{tag}`"))
+            .collect::<Vec<_>>();
+        if !tags.is_empty() {
+            self.push(&tags.join(" "));
+        }
+
+        match &doc.description {
+            Some(desc) => self.push(&self.text(desc)),
+            None => self.push("*No description provided*"),
+        }
+        for (title, params) in [
+            ("Parameter", &doc.params),
+            ("Template parameter", &doc.tparams),
+        ] {
+            if params.is_empty() {
+                continue;
+            }
+            let mut table = format!("| {title} | Description |\n| --- | --- |\n");
+            for param in params {
+                writeln!(
+                    table,
+                    "| `{}` | {} |",
+                    table_cell(&param.name),
+                    table_cell(&param.description)
+                )
+                .unwrap();
+            }
+            self.push(&table);
+        }
+        if let Some(ret) = &doc.returns {
+            self.push(&format!("**Returns:** {ret}"));
+        }
+        if let Some(throws) = &doc.throws {
+            self.push(&format!("**Throws:** {throws}"));
+        }
+        if !doc.see.is_empty() {
+            let list = doc
+                .see
+                .iter()
+                .map(|see| format!("- {}", self.see(see)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.push(&format!("**See also:**\n\n{list}"));
+        }
+        for note in &doc.notes {
+            self.push(&format!("> ℹ {note}"));
+        }
+        for warning in &doc.warnings {
+            self.push(&format!("> ⚠️ {warning}"));
+        }
+        if include_examples {
+            for example in &doc.examples {
+                self.code(example);
+            }
+        }
+    }
+
+    /// A member with its declaration and docs. The anchor matches the one
+    /// the HTML pages use, so links with fragments keep working
+    pub fn member(
+        &mut self,
+        name: &str,
+        anchor: Option<String>,
+        decl: &str,
+        comment: Option<String>,
+    ) {
+        self.member_decl(name, anchor, decl);
+        self.doc(comment, true);
+    }
+
+    fn member_decl(&mut self, name: &str, anchor: Option<String>, decl: &str) {
+        if let Some(anchor) = anchor {
+            self.push(&format!("<a id=\"{anchor}\"></a>"));
+        }
+        self.heading(3, &format!("`{name}`"));
+        self.code(decl);
+    }
+
+    /// `text` as code, linking to the docs of an entity if it has any
+    pub fn code_link(&self, entity: &Entity, text: &str) -> String {
+        match self.entity_link(entity) {
+            Some(link) => format!("[`{text}`]({link})"),
+            None => format!("`{text}`"),
+        }
+    }
+
+    /// A section listing some items. Empty sections are left out
+    pub fn section<T>(&mut self, title: &str, items: Vec<T>, mut add: impl FnMut(&mut Self, T)) {
+        if items.is_empty() {
+            return;
+        }
+        self.heading(2, title);
+        for item in items {
+            add(self, item);
+        }
+    }
+
+    pub fn method(&mut self, fun: Entity) {
+        self.member_decl(
+            &fun.get_name().unwrap_or("_anon".into()),
+            member_fun_link(&fun),
+            &fun_signature(&fun),
+        );
+        let overridden = fun
+            .get_overridden_methods()
+            .unwrap_or_default()
+            .iter()
+            .map(|base| self.code_link(base, &base.full_name().join("::")))
+            .collect::<Vec<_>>();
+        if !overridden.is_empty() {
+            self.push(&format!("Overrides {}", overridden.join(", ")));
+        }
+        self.doc(fun.get_doc_comment(), true);
+    }
+
+    fn variable(&mut self, var: &Entity) {
+        self.member(
+            &var.get_name().unwrap_or("_anon".into()),
+            var.get_name(),
+            &variable_decl(var),
+            var.get_comment(),
+        );
+    }
+
+    /// List item with a link to an entity and the summary of its docs
+    fn summary_item(&mut self, link: &str, entity: &Entity) {
+        let brief = entity
+            .get_doc_comment()
+            .and_then(|s| JSDocComment::parse(s, self.builder).brief())
+            .map(|brief| self.text(&brief))
+            .unwrap_or("*No description provided*".into());
+        self.list_item(&format!("{link} - {}", table_cell(&brief)));
+    }
+
+    /// Entries of a namespace with a link and a summary of each
+    pub fn entry_list(&mut self, title: &str, entries: Vec<&dyn ASTEntry<'e>>) {
+        self.section(title, entries, |page, entry| {
+            let link = format!("[`{}`]({})", entry.name(), page.link(&entry.url()));
+            page.summary_item(&link, entry.entity());
+        });
+    }
+
+    /// Links to the members a class inherits from one of its bases
+    pub fn inherited(&mut self, inherited: InheritedMembers) {
+        let members = inherited
+            .functions
+            .into_iter()
+            .map(|(fun, access)| (fun, fun_signature(&fun), access))
+            .chain(
+                inherited
+                    .fields
+                    .into_iter()
+                    .map(|(field, access)| (field, variable_decl(&field), access)),
+            )
+            .collect::<Vec<_>>();
+        let title = format!("Inherited from `{}`", inherited.base.full_name().join("::"));
+        self.section(&title, members, |page, (member, decl, access)| {
+            let name = page.code_link(&member, &member.get_name().unwrap_or("_anon".into()));
+            let access = if access == Accessibility::Protected {
+                " (protected)"
+            } else {
+                ""
+            };
+            let decl = decl.split_whitespace().collect::<Vec<_>>().join(" ");
+            page.list_item(&format!("{name}{access} - `{decl}`"));
+        });
+    }
+
+    pub fn namespace_entries(&mut self, ns: &Namespace<'e>) {
+        for (title, category) in [
+            ("Namespaces", "namespace"),
+            ("Classes", "class"),
+            ("Structs", "struct"),
+            ("Enums", "enum"),
+            ("Type aliases", "alias"),
+            ("Functions", "function"),
+            ("Variables", "variable"),
+            ("Macros", "macro"),
+        ] {
+            let mut entries = ns
+                .entries
+                .values()
+                .filter(|entry| entry.category() == category)
+                .map(|entry| entry as &dyn ASTEntry<'e>)
+                .collect::<Vec<_>>();
+            entries.sort_by_key(|entry| entry.name());
+            self.entry_list(title, entries);
+        }
+    }
+}
+
+pub fn markdown_classlike<'e, T: ASTEntry<'e>>(entry: &T, builder: &'e Builder<'e>) -> String {
+    let class = entry.entity();
+    let mut page = MarkdownPage::new(builder, entry.url());
+    page.title(
+        if entry.category() == "struct" {
+            "Struct"
+        } else {
+            "Class"
+        },
+        entry,
+    );
+    page.code(&classlike_decl(class, entry.category()));
+    page.doc(class.get_doc_comment(), true);
+
+    page.section(
+        "Public static methods",
+        class.get_member_functions(Access::Public, Include::Statics),
+        MarkdownPage::method,
+    );
+    page.section(
+        "Public static members",
+        get_fields(class, EntityKind::VarDecl, Accessibility::Public),
+        |page, var| page.variable(&var),
+    );
+    page.section(
+        "Public member functions",
+        class.get_member_functions(Access::Public, Include::Members),
+        MarkdownPage::method,
+    );
+    page.section(
+        "Fields",
+        get_fields(class, EntityKind::FieldDecl, Accessibility::Public),
+        |page, var| page.variable(&var),
+    );
+    page.section(
+        "Protected member functions",
+        class.get_member_functions(Access::Protected, Include::Members),
+        MarkdownPage::method,
+    );
+    page.section(
+        "Protected static members",
+        get_fields(class, EntityKind::VarDecl, Accessibility::Protected),
+        |page, var| page.variable(&var),
+    );
+    page.section(
+        "Protected fields",
+        get_fields(class, EntityKind::FieldDecl, Accessibility::Protected),
+        |page, var| page.variable(&var),
+    );
+    for inherited in get_inherited_members(class) {
+        page.inherited(inherited);
+    }
+    page.section(
+        "Derived classes",
+        builder.hierarchy.derived(class),
+        |page, derived| {
+            let link = page.code_link(&derived, &derived.get_name().unwrap_or("_anon".into()));
+            page.summary_item(&link, &derived);
+        },
+    );
+
+    page.finish()
+}
+
+pub fn markdown_function<'e>(entry: &Function<'e>, builder: &'e Builder<'e>) -> String {
+    let mut page = MarkdownPage::new(builder, entry.url());
+    page.title("Function", entry);
+    if let [fun] = entry.overloads() {
+        page.code(&fun_signature(fun));
+        page.doc(fun.get_doc_comment(), true);
+    } else {
+        page.push(&format!(
+            "`{}` has {} overloads",
+            entry.name(),
+            entry.overloads().len()
+        ));
+        page.section(
+            "Overloads",
+            entry.overloads().to_vec(),
+            MarkdownPage::method,
+        );
+    }
+    page.finish()
+}
+
+pub fn markdown_enum<'e, T: ASTEntry<'e>>(entry: &T, builder: &'e Builder<'e>) -> String {
+    let mut page = MarkdownPage::new(builder, entry.url());
+    page.title("Enum", entry);
+    page.code(&enum_decl(entry.entity()));
+    page.doc(entry.entity().get_doc_comment(), true);
+    page.section(
+        "Enumerators",
+        entry
+            .entity()
+            .get_children()
+            .into_iter()
+            .filter(|child| child.get_kind() == EntityKind::EnumConstantDecl)
+            .collect(),
+        |page, enumerator| {
+            page.member(
+                &enumerator.get_name().unwrap_or("_anon".into()),
+                enumerator.get_name(),
+                &enumerator_decl(&enumerator),
+                enumerator.get_comment(),
+            )
+        },
+    );
+    page.finish()
+}
+
+/// Pages for entities that are just a declaration and its docs
+pub fn markdown_declaration<'e, T: ASTEntry<'e>>(
+    entry: &T,
+    builder: &'e Builder<'e>,
+    kind: &str,
+) -> String {
+    let entity = entry.entity();
+    let mut page = MarkdownPage::new(builder, entry.url());
+    page.title(kind, entry);
+    page.code(&match entity.get_kind() {
+        EntityKind::MacroDefinition => macro_decl(entity),
+        EntityKind::VarDecl => variable_decl(entity),
+        _ => alias_decl(entity),
+    });
+    page.doc(entity.get_doc_comment(), true);
+    page.finish()
+}
+
+pub fn markdown_namespace<'e>(ns: &Namespace<'e>, builder: &'e Builder<'e>) -> String {
+    let mut page = MarkdownPage::new(builder, ns.url());
+    page.title("Namespace", ns);
+    page.doc(ns.entity().get_doc_comment(), true);
+    page.namespace_entries(ns);
+    page.finish()
+}
+
+/// Tutorials are copied with the links fixed up and the metadata removed
+pub fn markdown_tutorial<'e>(
+    builder: &'e Builder<'e>,
+    url: UrlPath,
+    title: &str,
+    content: &str,
+) -> MarkdownPage<'e> {
+    let mut page = MarkdownPage::new(builder, url);
+    let (content, _) = parse_markdown_metadata(content);
+    // The title may come from the metadata instead of the first heading
+    if !content.trim_start().starts_with('#') {
+        page.heading(1, title);
+    }
+    page.push(&page.fix_links(content.trim()));
+    page
+}
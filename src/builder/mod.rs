@@ -1,12 +1,28 @@
+pub mod alias;
 #[allow(clippy::module_inception)]
 pub mod builder;
+pub mod cache;
 pub mod class;
 pub mod comment;
+pub mod coverage;
+pub mod diff;
+pub mod enum_;
+pub mod export;
 pub mod files;
 pub mod function;
+pub mod hierarchy;
+pub mod includes;
+pub mod lint;
+pub mod links;
+pub mod macro_;
 pub mod markdown;
+pub mod markdown_output;
 pub mod namespace;
+pub mod reference;
+pub mod search;
 pub mod shared;
+pub mod signature;
 pub mod struct_;
 pub mod traits;
 pub mod tutorial;
+pub mod variable;
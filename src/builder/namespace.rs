@@ -3,14 +3,25 @@ use std::{collections::HashMap, sync::Arc};
 use clang::{Entity, EntityKind};
 use log::{debug, warn};
 
-use crate::{config::Config, url::UrlPath};
+use crate::{
+    config::Config,
+    html::{Html, template::Template},
+    url::UrlPath,
+};
 
 use super::{
+    alias::Alias,
     builder::Builder,
     class::Class,
+    enum_::Enum,
+    export::{ApiEntity, page_context},
     function::Function,
+    macro_::Macro,
+    markdown_output::markdown_namespace,
+    shared::output_namespace,
     struct_::Struct,
-    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem},
+    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
+    variable::Variable,
 };
 
 pub enum CppItemKind {
@@ -18,6 +29,10 @@ pub enum CppItemKind {
     Class,
     Struct,
     Function,
+    Enum,
+    Alias,
+    Variable,
+    Macro,
 }
 
 impl CppItemKind {
@@ -28,6 +43,12 @@ impl CppItemKind {
             | EntityKind::ClassTemplate
             | EntityKind::ClassTemplatePartialSpecialization => Some(Self::Class),
             EntityKind::FunctionDecl | EntityKind::FunctionTemplate => Some(Self::Function),
+            EntityKind::EnumDecl => Some(Self::Enum),
+            EntityKind::TypedefDecl
+            | EntityKind::TypeAliasDecl
+            | EntityKind::TypeAliasTemplateDecl => Some(Self::Alias),
+            EntityKind::VarDecl => Some(Self::Variable),
+            EntityKind::MacroDefinition => Some(Self::Macro),
             EntityKind::Namespace => Some(Self::Namespace),
             _ => None,
         }
@@ -39,6 +60,10 @@ impl CppItemKind {
             Self::Class => "classes",
             Self::Struct => "classes",
             Self::Function => "functions",
+            Self::Enum => "enums",
+            Self::Alias => "aliases",
+            Self::Variable => "variables",
+            Self::Macro => "macros",
         })
     }
 }
@@ -48,6 +73,10 @@ pub enum CppItem<'e> {
     Class(Class<'e>),
     Struct(Struct<'e>),
     Function(Function<'e>),
+    Enum(Enum<'e>),
+    Alias(Alias<'e>),
+    Variable(Variable<'e>),
+    Macro(Macro<'e>),
 }
 
 impl<'e> CppItem<'e> {
@@ -80,6 +109,26 @@ impl<'e> CppItem<'e> {
                     out.push(fun);
                 }
             }
+            CppItem::Enum(en) => {
+                if matcher(en) {
+                    out.push(en);
+                }
+            }
+            CppItem::Alias(al) => {
+                if matcher(al) {
+                    out.push(al);
+                }
+            }
+            CppItem::Variable(var) => {
+                if matcher(var) {
+                    out.push(var);
+                }
+            }
+            CppItem::Macro(mac) => {
+                if matcher(mac) {
+                    out.push(mac);
+                }
+            }
         }
     }
 }
@@ -91,6 +140,10 @@ impl<'e> Entry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.name(),
             CppItem::Struct(st) => st.name(),
             CppItem::Function(st) => st.name(),
+            CppItem::Enum(en) => en.name(),
+            CppItem::Alias(al) => al.name(),
+            CppItem::Variable(var) => var.name(),
+            CppItem::Macro(mac) => mac.name(),
         }
     }
 
@@ -100,6 +153,10 @@ impl<'e> Entry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.url(),
             CppItem::Struct(st) => st.url(),
             CppItem::Function(st) => st.url(),
+            CppItem::Enum(en) => en.url(),
+            CppItem::Alias(al) => al.url(),
+            CppItem::Variable(var) => var.url(),
+            CppItem::Macro(mac) => mac.url(),
         }
     }
 
@@ -109,6 +166,10 @@ impl<'e> Entry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.build(builder),
             CppItem::Struct(st) => st.build(builder),
             CppItem::Function(st) => st.build(builder),
+            CppItem::Enum(en) => en.build(builder),
+            CppItem::Alias(al) => al.build(builder),
+            CppItem::Variable(var) => var.build(builder),
+            CppItem::Macro(mac) => mac.build(builder),
         }
     }
 
@@ -118,6 +179,10 @@ impl<'e> Entry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.nav(),
             CppItem::Struct(st) => st.nav(),
             CppItem::Function(st) => st.nav(),
+            CppItem::Enum(en) => en.nav(),
+            CppItem::Alias(al) => al.nav(),
+            CppItem::Variable(var) => var.nav(),
+            CppItem::Macro(mac) => mac.nav(),
         }
     }
 }
@@ -129,6 +194,23 @@ impl<'e> ASTEntry<'e> for CppItem<'e> {
             CppItem::Function(c) => c.entity(),
             CppItem::Namespace(c) => c.entity(),
             CppItem::Struct(c) => c.entity(),
+            CppItem::Enum(c) => c.entity(),
+            CppItem::Alias(c) => c.entity(),
+            CppItem::Variable(c) => c.entity(),
+            CppItem::Macro(c) => c.entity(),
+        }
+    }
+
+    fn entities(&self) -> Vec<Entity<'e>> {
+        match self {
+            CppItem::Class(c) => c.entities(),
+            CppItem::Function(c) => c.entities(),
+            CppItem::Namespace(c) => c.entities(),
+            CppItem::Struct(c) => c.entities(),
+            CppItem::Enum(c) => c.entities(),
+            CppItem::Alias(c) => c.entities(),
+            CppItem::Variable(c) => c.entities(),
+            CppItem::Macro(c) => c.entities(),
         }
     }
 
@@ -138,6 +220,10 @@ impl<'e> ASTEntry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.category(),
             CppItem::Struct(st) => st.category(),
             CppItem::Function(st) => st.category(),
+            CppItem::Enum(en) => en.category(),
+            CppItem::Alias(al) => al.category(),
+            CppItem::Variable(var) => var.category(),
+            CppItem::Macro(mac) => mac.category(),
         }
     }
 }
@@ -172,16 +258,22 @@ impl<'e> Namespace<'e> {
 
     fn merge_with_namespace(&mut self, other: Namespace<'e>) {
         assert_eq!(self.entity.get_name(), other.entity.get_name());
+        // Namespaces are usually only documented in one of their blocks
+        if self.entity.get_comment().is_none() && other.entity.get_comment().is_some() {
+            self.entity = other.entity;
+        }
         for (name, other_entry) in other.entries {
-            if matches!(other_entry, CppItem::Namespace(_))
-                && let Some(CppItem::Namespace(ns)) = self.entries.get_mut(&name)
-            {
-                let CppItem::Namespace(entry_ns) = other_entry else {
-                    unreachable!()
-                };
-                ns.merge_with_namespace(entry_ns);
-            } else {
-                self.entries.insert(name, other_entry);
+            match (other_entry, self.entries.get_mut(&name)) {
+                (CppItem::Namespace(entry_ns), Some(CppItem::Namespace(ns))) => {
+                    ns.merge_with_namespace(entry_ns);
+                }
+                // Overloads may be spread out across different namespace blocks
+                (CppItem::Function(entry_fun), Some(CppItem::Function(fun))) => {
+                    fun.merge_with_function(entry_fun);
+                }
+                (other_entry, _) => {
+                    self.entries.insert(name, other_entry);
+                }
             }
         }
     }
@@ -223,10 +315,14 @@ impl<'e> Namespace<'e> {
                 continue;
             }
 
-            // skips specialization of std stuff or builtin stuff
+            // skips specialization of std stuff or builtin stuff, and
+            // anonymous enums and structs since those would all get the same
+            // name and URL. Depending on the libclang version they're called
+            // `(anonymous enum at ...)` or `(unnamed enum at ...)`
             if full_child_name.starts_with("std::")
                 || child_name.contains("deduction guide for")
                 || child_name.contains("unnamed ")
+                || child_name.contains("anonymous ")
             {
                 continue;
             }
@@ -284,8 +380,50 @@ impl<'e> Namespace<'e> {
                     }
 
                     CppItemKind::Function => {
-                        let entry = Function::new(*child);
-                        self.entries.insert(entry.name(), CppItem::Function(entry));
+                        // Collect overloads into one entry
+                        if let Some(CppItem::Function(fun)) = self.entries.get_mut(&child_name) {
+                            fun.add_overload(*child);
+                        } else {
+                            let entry = Function::new(*child);
+                            self.entries.insert(entry.name(), CppItem::Function(entry));
+                        }
+                    }
+
+                    CppItemKind::Enum => {
+                        if child.is_definition() {
+                            let entry = Enum::new(*child);
+                            self.entries.insert(entry.name(), CppItem::Enum(entry));
+                        }
+                    }
+
+                    CppItemKind::Alias => {
+                        // skip C-style `typedef struct Foo Foo;` since that
+                        // would just overwrite the struct itself
+                        let aliases_same_name = child
+                            .get_typedef_underlying_type()
+                            .and_then(|t| t.get_declaration())
+                            .and_then(|d| d.get_name())
+                            .is_some_and(|name| name == child_name);
+                        if !aliases_same_name {
+                            let entry = Alias::new(*child);
+                            self.entries.insert(entry.name(), CppItem::Alias(entry));
+                        }
+                    }
+
+                    CppItemKind::Variable => {
+                        let entry = Variable::new(*child);
+                        self.entries.insert(entry.name(), CppItem::Variable(entry));
+                    }
+
+                    CppItemKind::Macro => {
+                        // Only document macros from the project's own headers,
+                        // and not include guards
+                        if child.config_source(config.clone()).is_some()
+                            && !child.is_include_guard()
+                        {
+                            let entry = Macro::new(*child);
+                            self.entries.insert(entry.name(), CppItem::Macro(entry));
+                        }
                     }
                 }
             }
@@ -301,11 +439,41 @@ impl<'e> Namespace<'e> {
         }
         res
     }
+
+    /// Find an entity by its fully qualified name, such as `noahh::Mod::getID`.
+    /// Members of classes are looked up through their parent
+    pub fn find_by_name(&'e self, name: &str) -> Option<Entity<'e>> {
+        let name = name.trim_start_matches("::").trim_end_matches("()");
+        let matches_name = |name: &str| {
+            let name = name.to_owned();
+            move |entry: &dyn ASTEntry<'e>| entry.entity().full_name().join("::") == name
+        };
+
+        if let Some(entry) = self.get(&matches_name(name)).first() {
+            return Some(*entry.entity());
+        }
+
+        let (parent, member) = name.rsplit_once("::")?;
+        self.get(&matches_name(parent))
+            .into_iter()
+            .find_map(|entry| {
+                entry
+                    .entity()
+                    .get_children()
+                    .into_iter()
+                    .find(|child| child.get_name().is_some_and(|n| n == member))
+            })
+    }
 }
 
 impl<'e> Entry<'e> for Namespace<'e> {
     fn build(&self, builder: &Builder<'e>) -> BuildResult {
-        let mut handles = Vec::new();
+        // The root namespace is covered by the index page
+        let mut handles = if self.is_root {
+            Vec::new()
+        } else {
+            builder.create_output_for(self)?
+        };
         for entry in self.entries.values() {
             handles.extend(entry.build(builder)?);
         }
@@ -355,3 +523,44 @@ impl<'e> ASTEntry<'e> for Namespace<'e> {
         "namespace"
     }
 }
+
+impl<'e> OutputEntry<'e> for Namespace<'e> {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.namespace.clone(),
+            output_namespace(self, builder),
+        )
+    }
+
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        markdown_namespace(self, builder)
+    }
+
+    fn context(&self, builder: &'e Builder<'e>) -> Option<serde_json::Value> {
+        let mut context = page_context(self, builder);
+        let mut entries = self
+            .entries
+            .values()
+            .map(|entry| ApiEntity::new(entry.entity(), entry.category(), builder))
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        context["entries"] = serde_json::json!(entries);
+        Some(context)
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        self.output_description(builder)
+    }
+
+    fn cache_key(&self, builder: &'e Builder<'e>) -> Option<u64> {
+        // The page summarizes every direct child
+        builder.cache.entities_key(
+            &self
+                .entries
+                .values()
+                .flat_map(|entry| entry.entities())
+                .chain([self.entity])
+                .collect::<Vec<_>>(),
+        )
+    }
+}
@@ -0,0 +1,471 @@
+use super::{
+    builder::Builder,
+    comment::JSDocComment,
+    export::ApiDoc,
+    shared::get_fields,
+    signature::{classlike_decl, fun_signature, variable_decl},
+    traits::{ASTEntry, Access, EntityMethods, Include},
+};
+use clang::{Accessibility, Entity, EntityKind};
+use std::{fmt::Write, fs, path::Path};
+
+/// Width plain text references are wrapped to
+const TEXT_WIDTH: usize = 80;
+
+struct RefItem {
+    decl: String,
+    doc: Option<ApiDoc>,
+}
+
+impl RefItem {
+    fn new(entity: &Entity, decl: String, builder: &Builder) -> Self {
+        Self {
+            decl,
+            doc: entity
+                .get_doc_comment()
+                .map(|c| JSDocComment::parse(c, builder).export()),
+        }
+    }
+}
+
+/// The reference for a single class or free function
+pub struct RefPage {
+    name: String,
+    kind: &'static str,
+    include: Option<String>,
+    brief: Option<String>,
+    synopsis: String,
+    doc: Option<ApiDoc>,
+    sections: Vec<(&'static str, Vec<RefItem>)>,
+}
+
+impl RefPage {
+    fn new<'e>(entry: &dyn ASTEntry<'e>, builder: &'e Builder<'e>) -> Self {
+        let entity = entry.entity();
+        let comment = entity
+            .get_doc_comment()
+            .map(|c| JSDocComment::parse(c, builder));
+        let mut page = Self {
+            name: entity.full_name().join("::"),
+            kind: entry.category(),
+            include: entity
+                .include_path(builder.config.clone())
+                .map(|p| p.to_raw_string()),
+            brief: comment.as_ref().and_then(|c| c.brief()),
+            synopsis: String::new(),
+            doc: comment.map(|c| c.export()),
+            sections: Vec::new(),
+        };
+
+        if entry.category() == "function" {
+            let overloads = entry.entities();
+            page.synopsis = overloads
+                .iter()
+                .map(fun_signature)
+                .collect::<Vec<_>>()
+                .join("\n");
+            // Each overload is described separately if there are several
+            if overloads.len() > 1 {
+                page.doc = None;
+                page.sections.push((
+                    "Overloads",
+                    overloads
+                        .iter()
+                        .map(|fun| RefItem::new(fun, fun_signature(fun), builder))
+                        .collect(),
+                ));
+            }
+            return page;
+        }
+
+        page.synopsis = classlike_decl(entity, entry.category());
+        let methods = |access, include| {
+            entity
+                .get_member_functions(access, include)
+                .iter()
+                .map(|fun| RefItem::new(fun, fun_signature(fun), builder))
+                .collect::<Vec<_>>()
+        };
+        let fields = |kind, access| {
+            get_fields(entity, kind, access)
+                .iter()
+                .map(|var| RefItem::new(var, variable_decl(var), builder))
+                .collect::<Vec<_>>()
+        };
+        page.sections = vec![
+            (
+                "Public static methods",
+                methods(Access::Public, Include::Statics),
+            ),
+            (
+                "Public static members",
+                fields(EntityKind::VarDecl, Accessibility::Public),
+            ),
+            (
+                "Public member functions",
+                methods(Access::Public, Include::Members),
+            ),
+            (
+                "Fields",
+                fields(EntityKind::FieldDecl, Accessibility::Public),
+            ),
+            (
+                "Protected member functions",
+                methods(Access::Protected, Include::Members),
+            ),
+            (
+                "Protected static members",
+                fields(EntityKind::VarDecl, Accessibility::Protected),
+            ),
+            (
+                "Protected fields",
+                fields(EntityKind::FieldDecl, Accessibility::Protected),
+            ),
+        ];
+        page.sections.retain(|(_, items)| !items.is_empty());
+        page
+    }
+
+    /// Name of the file the page is saved as, without an extension. Colons
+    /// aren't allowed in file names on Windows
+    pub fn file_name(&self) -> String {
+        self.name.replace("::", ".")
+    }
+}
+
+/// Quick reference for every class, struct and free function in the API,
+/// for reading in a terminal
+pub struct Reference {
+    project: String,
+    version: String,
+    pages: Vec<RefPage>,
+}
+
+impl Reference {
+    pub fn new<'e>(builder: &'e Builder<'e>) -> Self {
+        let mut pages = builder
+            .root
+            .get(&|entry| matches!(entry.category(), "class" | "struct" | "function"))
+            .into_iter()
+            .map(|entry| RefPage::new(entry, builder))
+            .collect::<Vec<_>>();
+        pages.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            project: builder.config.project.name.clone(),
+            version: builder.config.project.version.clone(),
+            pages,
+        }
+    }
+
+    /// Write a `.txt` file for every page and an `index.txt` listing them
+    pub fn write_text(&self, dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Unable to create text reference dir: {e}"))?;
+
+        let mut index = format!("{} {} API reference\n\n", self.project, self.version);
+        for page in &self.pages {
+            writeln!(index, "{} ({})", page.name, page.kind).unwrap();
+            if let Some(ref brief) = page.brief {
+                index += &wrap(brief, 4);
+            }
+            writeln!(index, "    See {}.txt", page.file_name()).unwrap();
+            index.push('\n');
+
+            fs::write(
+                dir.join(format!("{}.txt", page.file_name())),
+                page.to_text(),
+            )
+            .map_err(|e| format!("Unable to save reference for {}: {e}", page.name))?;
+        }
+        fs::write(dir.join("index.txt"), index.trim_end().to_owned() + "\n")
+            .map_err(|e| format!("Unable to save reference index: {e}"))
+    }
+
+    /// Write a section 3 man page for every page
+    pub fn write_man(&self, dir: &Path) -> Result<(), String> {
+        let dir = dir.join("man3");
+        fs::create_dir_all(&dir).map_err(|e| format!("Unable to create man page dir: {e}"))?;
+        for page in &self.pages {
+            fs::write(
+                dir.join(format!("{}.3", page.file_name())),
+                page.to_man(&self.project, &self.version),
+            )
+            .map_err(|e| format!("Unable to save man page for {}: {e}", page.name))?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrap the paragraphs of some text. Code blocks and lists are left alone
+/// since their line breaks mean something
+fn wrap(text: &str, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    let mut res = String::new();
+    let mut in_code = false;
+    for para in text.trim().split("\n\n") {
+        let verbatim = in_code
+            || para.contains("```")
+            || para
+                .lines()
+                .any(|line| line.starts_with(['-', '*', ' ', '\t', '|', '>']));
+        in_code ^= para.matches("```").count() % 2 == 1;
+
+        if verbatim {
+            for line in para.lines() {
+                writeln!(res, "{pad}{}", line.trim_end()).unwrap();
+            }
+        } else {
+            let mut line = String::new();
+            for word in para.split_whitespace() {
+                if !line.is_empty() && indent + line.len() + 1 + word.len() > TEXT_WIDTH {
+                    writeln!(res, "{pad}{line}").unwrap();
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line += word;
+            }
+            writeln!(res, "{pad}{line}").unwrap();
+        }
+        res.push('\n');
+    }
+    res.trim_end().to_owned() + "\n"
+}
+
+fn indent_lines(text: &str, indent: usize) -> String {
+    text.lines()
+        .map(|line| format!("{}{line}\n", " ".repeat(indent)))
+        .collect()
+}
+
+fn doc_to_text(doc: &Option<ApiDoc>, indent: usize) -> String {
+    let Some(doc) = doc else {
+        return wrap("No description provided", indent);
+    };
+    let mut res = String::new();
+    let tags = doc
+        .version
+        .iter()
+        .map(|v| format!("Version {v}"))
+        .chain(doc.since.iter().map(|v| format!("Since {v}")))
+        .chain(doc.short_notes.iter().cloned())
+        .map(|tag| format!("[{tag}]"))
+        .collect::<Vec<_>>();
+    if !tags.is_empty() {
+        res += &wrap(&tags.join(" "), indent);
+        res.push('\n');
+    }
+    res += &wrap(
+        doc.description
+            .as_deref()
+            .unwrap_or("No description provided"),
+        indent,
+    );
+
+    for (title, params) in [
+        ("Parameters", &doc.params),
+        ("Template parameters", &doc.tparams),
+    ] {
+        if params.is_empty() {
+            continue;
+        }
+        let width = params.iter().map(|p| p.name.len()).max().unwrap_or(0);
+        writeln!(res, "\n{}{title}:", " ".repeat(indent)).unwrap();
+        for param in params {
+            let desc = wrap(&param.description, indent + width + 4);
+            writeln!(
+                res,
+                "{}{:width$}  {}",
+                " ".repeat(indent + 2),
+                param.name,
+                desc.trim_start().trim_end()
+            )
+            .unwrap();
+        }
+    }
+    for (title, value) in [("Returns", &doc.returns), ("Throws", &doc.throws)] {
+        if let Some(value) = value {
+            res.push('\n');
+            res += &wrap(&format!("{title}: {value}"), indent);
+        }
+    }
+    if !doc.see.is_empty() {
+        res.push('\n');
+        res += &wrap(&format!("See also: {}", doc.see.join(", ")), indent);
+    }
+    for (title, list) in [("Note", &doc.notes), ("Warning", &doc.warnings)] {
+        for item in list {
+            res.push('\n');
+            res += &wrap(&format!("{title}: {item}"), indent);
+        }
+    }
+    for example in &doc.examples {
+        writeln!(res, "\n{}Example:", " ".repeat(indent)).unwrap();
+        res += &indent_lines(example.trim_matches('\n'), indent + 4);
+    }
+    res
+}
+
+/// Escape text for troff. Lines starting with a control character would be
+/// read as requests
+fn troff_escape(text: &str) -> String {
+    text.replace('\\', "\\e")
+        .replace('-', "\\-")
+        .lines()
+        .map(|line| {
+            if line.starts_with(['.', '\'']) {
+                format!("\\&{line}\n")
+            } else {
+                format!("{line}\n")
+            }
+        })
+        .collect()
+}
+
+/// Text that is shown as-is, without being filled
+fn troff_verbatim(text: &str) -> String {
+    format!(".nf\n{}.fi\n", troff_escape(text.trim_matches('\n')))
+}
+
+fn doc_to_man(doc: &Option<ApiDoc>) -> String {
+    let Some(doc) = doc else {
+        return troff_escape("No description provided");
+    };
+    let mut res = String::new();
+    let tags = doc
+        .version
+        .iter()
+        .map(|v| format!("Version {v}"))
+        .chain(doc.since.iter().map(|v| format!("Since {v}")))
+        .chain(doc.short_notes.iter().cloned())
+        .collect::<Vec<_>>();
+    if !tags.is_empty() {
+        writeln!(res, ".I\n{}.PP", troff_escape(&tags.join(", "))).unwrap();
+    }
+    for para in doc
+        .description
+        .as_deref()
+        .unwrap_or("No description provided")
+        .trim()
+        .split("\n\n")
+    {
+        if para.contains("```") {
+            res += &troff_verbatim(&para.replace("```cpp", "").replace("```", ""));
+        } else {
+            res += &troff_escape(para);
+        }
+        res += ".PP\n";
+    }
+
+    for (title, params) in [
+        ("Parameters", &doc.params),
+        ("Template parameters", &doc.tparams),
+    ] {
+        if params.is_empty() {
+            continue;
+        }
+        writeln!(res, ".B {title}:").unwrap();
+        for param in params {
+            write!(
+                res,
+                ".TP\n.I {}\n{}",
+                troff_escape(&param.name).trim_end(),
+                troff_escape(&param.description)
+            )
+            .unwrap();
+        }
+        res += ".PP\n";
+    }
+    for (title, value) in [("Returns", &doc.returns), ("Throws", &doc.throws)] {
+        if let Some(value) = value {
+            write!(res, ".B {title}:\n{}.PP\n", troff_escape(value)).unwrap();
+        }
+    }
+    if !doc.see.is_empty() {
+        write!(
+            res,
+            ".B See also:\n{}.PP\n",
+            troff_escape(&doc.see.join(", "))
+        )
+        .unwrap();
+    }
+    for (title, list) in [("Note", &doc.notes), ("Warning", &doc.warnings)] {
+        for item in list {
+            write!(res, ".B {title}:\n{}.PP\n", troff_escape(item)).unwrap();
+        }
+    }
+    for example in &doc.examples {
+        write!(
+            res,
+            ".B Example:\n.RS\n{}.RE\n.PP\n",
+            troff_verbatim(example)
+        )
+        .unwrap();
+    }
+    res
+}
+
+impl RefPage {
+    pub fn to_text(&self) -> String {
+        let mut res = format!("{} ({})\n", self.name, self.kind);
+        if let Some(ref include) = self.include {
+            writeln!(res, "#include <{include}>").unwrap();
+        }
+        res.push('\n');
+        res += &indent_lines(&self.synopsis, 4);
+        res.push('\n');
+        if self.doc.is_some() || self.sections.is_empty() {
+            res += &doc_to_text(&self.doc, 0);
+        }
+
+        for (title, items) in &self.sections {
+            writeln!(res, "\n{}\n", title.to_uppercase()).unwrap();
+            for item in items {
+                res += &indent_lines(&item.decl, 2);
+                res += &doc_to_text(&item.doc, 6);
+                res.push('\n');
+            }
+        }
+        res.trim_end().to_owned() + "\n"
+    }
+
+    pub fn to_man(&self, project: &str, version: &str) -> String {
+        let mut res = format!(
+            ".TH \"{}\" 3 \"\" \"{project} {version}\" \"{project} API Reference\"\n",
+            self.name
+        );
+        write!(
+            res,
+            ".SH NAME\n{}",
+            troff_escape(&match self.brief {
+                Some(ref brief) => format!("{} - {brief}", self.name),
+                None => format!("{} - {}", self.name, self.kind),
+            })
+        )
+        .unwrap();
+
+        res += ".SH SYNOPSIS\n";
+        if let Some(ref include) = self.include {
+            write!(res, ".B {}", troff_escape(&format!("#include <{include}>"))).unwrap();
+            res += ".PP\n";
+        }
+        res += &troff_verbatim(&self.synopsis);
+
+        if self.doc.is_some() || self.sections.is_empty() {
+            res += ".SH DESCRIPTION\n";
+            res += &doc_to_man(&self.doc);
+        }
+
+        for (title, items) in &self.sections {
+            writeln!(res, ".SH {}", title.to_uppercase()).unwrap();
+            for item in items {
+                res += ".PP\n";
+                res += &troff_verbatim(&item.decl);
+                res += ".RS\n";
+                res += &doc_to_man(&item.doc);
+                res += ".RE\n";
+            }
+        }
+        res
+    }
+}
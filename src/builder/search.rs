@@ -0,0 +1,189 @@
+use super::{
+    builder::Builder,
+    comment::JSDocComment,
+    markdown::extract_sections_from_md,
+    namespace::{CppItem, Namespace},
+    traits::{ASTEntry, Access, EntityMethods, Entry, Include},
+    tutorial::TutorialFolder,
+};
+use crate::url::UrlPath;
+use clang::{Accessibility, Entity, EntityKind};
+use serde::Serialize;
+
+/// How many entries go into a single search index file
+const SHARD_SIZE: usize = 2500;
+
+#[derive(Serialize)]
+pub struct SearchEntry {
+    name: String,
+    full_name: String,
+    kind: &'static str,
+    url: String,
+    /// Lowercase words that make up the name, so `getModID` can be found
+    /// with `mod id`
+    tokens: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+/// Split an identifier into lowercase words on `::`, `_` and camelCase
+/// boundaries
+fn tokenize(name: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in name.split(|c: char| !c.is_alphanumeric()) {
+        let mut current = String::new();
+        let mut prev_lower = false;
+        for c in word.chars() {
+            if c.is_uppercase() && prev_lower && !current.is_empty() {
+                tokens.push(current.to_lowercase());
+                current.clear();
+            }
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+            current.push(c);
+        }
+        if !current.is_empty() {
+            tokens.push(current.to_lowercase());
+        }
+    }
+    tokens.sort();
+    tokens.dedup();
+    tokens
+}
+
+pub struct SearchIndex<'e> {
+    builder: &'e Builder<'e>,
+    entries: Vec<SearchEntry>,
+}
+
+impl<'e> SearchIndex<'e> {
+    pub fn new(builder: &'e Builder<'e>, tutorials: &TutorialFolder) -> Self {
+        let mut index = Self {
+            builder,
+            entries: Vec::new(),
+        };
+        index.add_namespace(&builder.root);
+        index.add_tutorials(tutorials);
+        index
+    }
+
+    /// Split the index into shards of at most `SHARD_SIZE` entries
+    pub fn shards(&self) -> Vec<&[SearchEntry]> {
+        self.entries.chunks(SHARD_SIZE).collect()
+    }
+
+    fn add(&mut self, entity: &Entity<'e>, kind: &'static str, url: String) {
+        let Some(name) = entity.get_name() else {
+            return;
+        };
+        let full_name = entity.full_name().join("::");
+        self.entries.push(SearchEntry {
+            tokens: tokenize(&full_name),
+            summary: entity
+                .get_doc_comment()
+                .and_then(|s| JSDocComment::parse(s, self.builder).brief()),
+            name,
+            full_name,
+            kind,
+            url,
+            text: None,
+        });
+    }
+
+    fn add_entry(&mut self, entry: &dyn ASTEntry<'e>) {
+        let url = entry
+            .url()
+            .to_absolute(self.builder.config.clone())
+            .to_string();
+        self.add(entry.entity(), entry.category(), url);
+    }
+
+    fn add_member(&mut self, member: &Entity<'e>, kind: &'static str) {
+        if let Some(url) = member.abs_docs_url(self.builder.config.clone()) {
+            self.add(member, kind, url.to_string());
+        }
+    }
+
+    fn add_namespace(&mut self, namespace: &'e Namespace<'e>) {
+        for entry in namespace.entries.values() {
+            self.add_entry(entry);
+            match entry {
+                CppItem::Namespace(ns) => self.add_namespace(ns),
+                CppItem::Class(_) | CppItem::Struct(_) => {
+                    let class = entry.entity();
+                    for fun in class.get_member_functions(Access::Public, Include::All) {
+                        self.add_member(&fun, "method");
+                    }
+                    for field in class.get_children().iter().filter(|child| {
+                        matches!(
+                            child.get_kind(),
+                            EntityKind::FieldDecl | EntityKind::VarDecl
+                        ) && child.get_accessibility() == Some(Accessibility::Public)
+                    }) {
+                        self.add_member(field, "field");
+                    }
+                }
+                CppItem::Enum(_) => {
+                    let url = entry.url().to_absolute(self.builder.config.clone());
+                    for enumerator in entry.entity().get_children() {
+                        if enumerator.get_kind() == EntityKind::EnumConstantDecl
+                            && let Some(name) = enumerator.get_name()
+                        {
+                            self.add(
+                                &enumerator,
+                                "enumerator",
+                                url.with_fragment(name).to_string(),
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn add_tutorial(&mut self, title: String, url: UrlPath, content: &str) {
+        let url = url.to_absolute(self.builder.config.clone());
+        let mut intro = Vec::new();
+        let mut sections = Vec::new();
+        for (heading, text) in extract_sections_from_md(content) {
+            match heading {
+                // The title heading is part of the tutorial itself
+                Some((heading, id)) if heading != title => sections.push(SearchEntry {
+                    tokens: tokenize(&heading),
+                    full_name: format!("{title} / {heading}"),
+                    name: heading,
+                    kind: "section",
+                    url: url.with_fragment(id).to_string(),
+                    summary: None,
+                    text: (!text.is_empty()).then_some(text),
+                }),
+                _ => intro.push(text),
+            }
+        }
+        let intro = intro.join(" ");
+        self.entries.push(SearchEntry {
+            tokens: tokenize(&title),
+            full_name: title.clone(),
+            name: title,
+            kind: "tutorial",
+            url: url.to_string(),
+            summary: None,
+            text: (!intro.trim().is_empty()).then_some(intro),
+        });
+        self.entries.extend(sections);
+    }
+
+    fn add_tutorials(&mut self, folder: &TutorialFolder) {
+        if let Some(index) = folder.index() {
+            self.add_tutorial(folder.name(), folder.url(), index);
+        }
+        for tutorial in folder.tutorials_sorted() {
+            self.add_tutorial(tutorial.name(), tutorial.url(), tutorial.content());
+        }
+        for subfolder in folder.folders_sorted() {
+            self.add_tutorials(subfolder);
+        }
+    }
+}
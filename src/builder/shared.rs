@@ -1,5 +1,8 @@
 use super::builder::Builder;
 use super::comment::JSDocComment;
+use super::function::Function;
+use super::hierarchy::HierarchyPage;
+use super::markdown::fmt_markdown;
 use super::namespace::CppItem;
 use super::traits::{ASTEntry, Access, EntityMethods, Entry, Include};
 use crate::annotation::Annotations;
@@ -9,6 +12,7 @@ use crate::html::{Html, HtmlElement, HtmlList, HtmlText};
 use clang::{Accessibility, Entity, EntityKind, Type, TypeKind};
 use multipeek::{IteratorExt, MultiPeek};
 use pulldown_cmark::CowStr;
+use std::collections::{HashSet, VecDeque};
 use std::str::Chars;
 use std::sync::Arc;
 
@@ -43,8 +47,127 @@ impl<T, Sep: Fn() -> T> InsertBetween<T, Sep> for Vec<T> {
     }
 }
 
-fn get_all_classes<'e>(namespace: &'e Namespace<'e>) -> Vec<&'e dyn ASTEntry<'e>> {
-    namespace.get(&|entry| matches!(entry.category(), "class" | "struct"))
+/// Fields or static members of a class with some access
+pub fn get_fields<'e>(class: &Entity<'e>, kind: EntityKind, access: Accessibility) -> Vec<Entity<'e>> {
+    class
+        .get_children()
+        .into_iter()
+        .filter(|child| child.get_kind() == kind && child.get_accessibility() == Some(access))
+        .collect()
+}
+
+/// Members a class inherits from one of its bases
+pub struct InheritedMembers<'e> {
+    /// The base class the members are declared in
+    pub base: Entity<'e>,
+    /// Inherited methods with the access they have in the derived class
+    pub functions: Vec<(Entity<'e>, Accessibility)>,
+    /// Inherited fields with the access they have in the derived class
+    pub fields: Vec<(Entity<'e>, Accessibility)>,
+}
+
+/// The definitions of the direct bases of a class with the access they are
+/// inherited with
+pub fn get_bases<'e>(class: &Entity<'e>) -> Vec<(Entity<'e>, Accessibility)> {
+    class
+        .get_children()
+        .into_iter()
+        .filter(|child| child.get_kind() == EntityKind::BaseSpecifier)
+        .filter_map(|base| {
+            let decl = base.get_type()?.get_declaration()?;
+            // Bases that are template specializations have their members
+            // in the template
+            let decl = decl.get_template().unwrap_or(decl);
+            Some((
+                decl.get_definition().unwrap_or(decl),
+                base.get_accessibility()?,
+            ))
+        })
+        .collect()
+}
+
+/// Anchors of every method declared in a class, including private ones since
+/// those still override methods of its bases
+fn get_method_links(class: &Entity) -> Vec<String> {
+    class
+        .get_children()
+        .iter()
+        .filter(|child| {
+            matches!(
+                child.get_kind(),
+                EntityKind::Method | EntityKind::FunctionTemplate
+            )
+        })
+        .filter_map(member_fun_link)
+        .collect()
+}
+
+/// The stricter of two accesses
+fn narrowest_access(a: Accessibility, b: Accessibility) -> Accessibility {
+    match (a, b) {
+        (Accessibility::Private, _) | (_, Accessibility::Private) => Accessibility::Private,
+        (Accessibility::Protected, _) | (_, Accessibility::Protected) => Accessibility::Protected,
+        _ => Accessibility::Public,
+    }
+}
+
+/// Walk the bases of a class and collect the public and protected members it
+/// inherits from each of them, nearest bases first. Members of private bases
+/// are not accessible and are left out, and virtual bases shared through
+/// several paths are only listed once. Methods that are overridden in the
+/// class or a nearer base are left out, as are fields that are hidden by one
+/// with the same name
+pub fn get_inherited_members<'e>(class: &Entity<'e>) -> Vec<InheritedMembers<'e>> {
+    let mut functions = get_method_links(class).into_iter().collect::<HashSet<_>>();
+    let mut fields = class
+        .get_children()
+        .into_iter()
+        .filter(|child| child.get_kind() == EntityKind::FieldDecl)
+        .filter_map(|field| field.get_name())
+        .collect::<HashSet<_>>();
+
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from(get_bases(class));
+    let mut res = Vec::new();
+    while let Some((base, access)) = queue.pop_front() {
+        if access == Accessibility::Private || !visited.insert(base.get_usr()) {
+            continue;
+        }
+        queue.extend(
+            get_bases(&base)
+                .into_iter()
+                .map(|(b, a)| (b, narrowest_access(access, a))),
+        );
+
+        let members = InheritedMembers {
+            base,
+            functions: base
+                .get_member_functions(Access::All, Include::All)
+                .into_iter()
+                // Every class has its own assignment operators
+                .filter(|fun| fun.get_name().is_none_or(|name| name != "operator="))
+                .filter(|fun| member_fun_link(fun).is_some_and(|link| !functions.contains(&link)))
+                .filter_map(|fun| Some((fun, narrowest_access(access, fun.get_accessibility()?))))
+                .collect(),
+            fields: base
+                .get_children()
+                .into_iter()
+                .filter(|child| {
+                    child.get_kind() == EntityKind::FieldDecl
+                        && child.get_accessibility() != Some(Accessibility::Private)
+                })
+                .filter(|field| field.get_name().is_some_and(|name| fields.insert(name)))
+                .filter_map(|field| {
+                    Some((field, narrowest_access(access, field.get_accessibility()?)))
+                })
+                .collect(),
+        };
+        functions.extend(get_method_links(&base));
+        if !members.functions.is_empty() || !members.fields.is_empty() {
+            res.push(members);
+        }
+    }
+    res
 }
 
 fn fmt_type(entity: &Type, builder: &Builder) -> Html {
@@ -71,6 +194,7 @@ fn fmt_type(entity: &Type, builder: &Builder) -> Html {
                                 EntityKind::TypedefDecl => "alias",
                                 EntityKind::UsingDeclaration => "alias",
                                 EntityKind::TypeAliasDecl => "alias",
+                                EntityKind::TypeAliasTemplateDecl => "alias",
                                 EntityKind::EnumDecl => "enum",
                                 _ => "type",
                             })
@@ -208,6 +332,7 @@ fn fmt_template_args(entity: &Entity, _builder: &Builder) -> Option<Html> {
 pub fn fmt_field(field: &Entity, builder: &Builder) -> Html {
     HtmlElement::new("details")
         .with_class("entity-desc")
+        .with_attr_opt("id", field.get_name())
         .with_child(
             HtmlElement::new("summary")
                 .with_classes(&["entity", "var"])
@@ -225,67 +350,110 @@ pub fn fmt_field(field: &Entity, builder: &Builder) -> Html {
         .into()
 }
 
+/// The declaration of a function with `name` in place of its name
+fn fmt_fun_decl(fun: &Entity, name: Html, builder: &Builder) -> Html {
+    HtmlElement::new("span")
+        .with_class("function-signature")
+        .with_child_opt(
+            fun.is_static_method()
+                .then_some(Html::span(&["keyword", "space-after"], "static")),
+        )
+        .with_child_opt(
+            fun.is_virtual_method()
+                .then_some(Html::span(&["keyword", "space-after"], "virtual")),
+        )
+        .with_child_opt(fun.get_result_type().map(|t| fmt_type(&t, builder)))
+        .with_child(name)
+        .with_child(
+            HtmlElement::new("span").with_class("params").with_children(
+                fun.get_function_arguments()
+                    .map(|args| {
+                        args.iter()
+                            .map(|arg| fmt_param(arg, builder))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or(Vec::new())
+                    .insert_between(|| Html::span(&["comma", "space-after"], ","))
+                    .surround(HtmlText::new("(").into(), HtmlText::new(")").into()),
+            ),
+        )
+        .with_child_opt(
+            fun.is_const_method()
+                .then_some(Html::span(&["keyword", "space-before"], "const")),
+        )
+        .with_child_opt(
+            fun.is_pure_virtual_method().then_some::<Html>(
+                HtmlList::new(vec![
+                    Html::span(&["space-before"], "="),
+                    Html::span(&["space-before", "literal"], "0"),
+                ])
+                .into(),
+            ),
+        )
+        .into()
+}
+
 fn fmt_fun_signature(fun: &Entity, builder: &Builder) -> Html {
     HtmlElement::new("summary")
         .with_classes(&["entity", "fun"])
         .with_child_opt(fmt_template_args(fun, builder))
-        .with_child(
-            HtmlElement::new("span")
-                .with_class("function-signature")
-                .with_child_opt(
-                    fun.is_static_method()
-                        .then_some(Html::span(&["keyword", "space-after"], "static")),
-                )
-                .with_child_opt(
-                    fun.is_virtual_method()
-                        .then_some(Html::span(&["keyword", "space-after"], "virtual")),
-                )
-                .with_child_opt(fun.get_result_type().map(|t| fmt_type(&t, builder)))
-                .with_child(Html::span(
-                    &["name", "space-before"],
-                    &fun.get_name().unwrap_or("_anon".into()),
-                ))
-                .with_child(
-                    HtmlElement::new("span").with_class("params").with_children(
-                        fun.get_function_arguments()
-                            .map(|args| {
-                                args.iter()
-                                    .map(|arg| fmt_param(arg, builder))
-                                    .collect::<Vec<_>>()
-                            })
-                            .unwrap_or(Vec::new())
-                            .insert_between(|| Html::span(&["comma", "space-after"], ","))
-                            .surround(HtmlText::new("(").into(), HtmlText::new(")").into()),
-                    ),
-                )
-                .with_child_opt(
-                    fun.is_const_method()
-                        .then_some(Html::span(&["keyword", "space-before"], "const")),
-                )
-                .with_child_opt(
-                    fun.is_pure_virtual_method().then_some::<Html>(
-                        HtmlList::new(vec![
-                            Html::span(&["space-before"], "="),
-                            Html::span(&["space-before", "literal"], "0"),
-                        ])
-                        .into(),
-                    ),
-                ),
+        .with_child(fmt_fun_decl(
+            fun,
+            Html::span(
+                &["name", "space-before"],
+                &fun.get_name().unwrap_or("_anon".into()),
+            ),
+            builder,
+        ))
+        .with_child_opt(
+            fun.is_override()
+                .then(|| Html::span(&["badge", "space-before"], "override")),
+        )
+        .with_child_opt(
+            fun.is_final()
+                .then(|| Html::span(&["badge", "space-before"], "final")),
         )
         .into()
 }
 
+/// Links to the base class methods a method overrides
+fn fmt_overrides(fun: &Entity, builder: &Builder) -> Option<Html> {
+    let overridden = fun.get_overridden_methods()?;
+    (!overridden.is_empty()).then(|| {
+        HtmlElement::new("p")
+            .with_class("overrides")
+            .with_child(HtmlText::new("Overrides "))
+            .with_children(
+                overridden
+                    .iter()
+                    .map(|base| {
+                        HtmlElement::new("a")
+                            .with_attr_opt("href", base.abs_docs_url(builder.config.clone()))
+                            .with_child(
+                                HtmlElement::new("code").with_text(base.full_name().join("::")),
+                            )
+                            .into()
+                    })
+                    .collect::<Vec<_>>()
+                    .insert_between(|| HtmlText::new(", ").into()),
+            )
+            .into()
+    })
+}
+
 pub fn fmt_class_method(fun: &Entity, builder: &Builder) -> Html {
     HtmlElement::new("details")
         .with_class("entity-desc")
         .with_attr_opt("id", member_fun_link(fun))
         .with_child(fmt_fun_signature(fun, builder))
         .with_child(
-            HtmlElement::new("div").with_child(
-                fun.get_comment()
-                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
-                    .unwrap_or(Html::span(&["no-desc"], "No description provided")),
-            ),
+            HtmlElement::new("div")
+                .with_child_opt(fmt_overrides(fun, builder))
+                .with_child(
+                    fun.get_doc_comment()
+                        .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                        .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+                ),
         )
         .into()
 }
@@ -316,8 +484,12 @@ pub fn fmt_classlike_decl(class: &Entity, keyword: &str, builder: &Builder) -> H
 }
 
 pub fn fmt_section(title: &str, data: Vec<Html>) -> Html {
+    fmt_section_with(title, data, true)
+}
+
+fn fmt_section_with(title: &str, data: Vec<Html>, open: bool) -> Html {
     HtmlElement::new("details")
-        .with_attr("open", "")
+        .with_attr_opt("open", open.then_some(""))
         .with_class("section")
         .with_child(
             HtmlElement::new("summary").with_child(
@@ -433,6 +605,335 @@ pub fn fmt_derived_class(entity: &Entity, builder: &Builder) -> Html {
         .into()
 }
 
+/// The name of an inherited member, linking to it on the page of its class
+fn fmt_inherited_name(member: &Entity, builder: &Builder) -> Html {
+    HtmlElement::new("a")
+        .with_classes(&["name", "space-before"])
+        .with_attr_opt("href", member.abs_docs_url(builder.config.clone()))
+        .with_child(HtmlText::new(member.get_name().unwrap_or("_anon".into())))
+        .into()
+}
+
+fn fmt_inherited_access(access: Accessibility) -> Option<Html> {
+    (access == Accessibility::Protected)
+        .then(|| Html::span(&["keyword", "space-after"], "protected"))
+}
+
+/// A collapsed section listing the members inherited from one base
+pub fn fmt_inherited_members(inherited: &InheritedMembers, builder: &Builder) -> Html {
+    let functions = inherited.functions.iter().map(|(fun, access)| {
+        HtmlElement::new("div")
+            .with_classes(&["entity", "fun"])
+            .with_child_opt(fmt_inherited_access(*access))
+            .with_child(fmt_fun_decl(fun, fmt_inherited_name(fun, builder), builder))
+            .into()
+    });
+    let fields = inherited.fields.iter().map(|(field, access)| {
+        HtmlElement::new("div")
+            .with_classes(&["entity", "var"])
+            .with_child_opt(fmt_inherited_access(*access))
+            .with_child_opt(field.get_type().map(|t| fmt_type(&t, builder)))
+            .with_child(fmt_inherited_name(field, builder))
+            .into()
+    });
+    fmt_section_with(
+        &format!("Inherited from {}", inherited.base.full_name().join("::")),
+        functions.chain(fields).collect(),
+        false,
+    )
+}
+
+pub fn fmt_enum_decl<'e, T: ASTEntry<'e>>(entry: &T, builder: &Builder) -> Html {
+    let underlying = entry.entity().get_enum_underlying_type();
+
+    HtmlElement::new("div")
+        .with_classes(&["entity", "class"])
+        .with_child(
+            HtmlElement::new("span")
+                .with_class("class-decl")
+                .with_child(Html::span(&["keyword", "space-after"], "enum"))
+                .with_child_opt(
+                    entry
+                        .entity()
+                        .is_scoped()
+                        .then_some(Html::span(&["keyword", "space-after"], "class")),
+                )
+                .with_child(Html::span(
+                    &["name"],
+                    entry.entity().get_name().unwrap_or("_".into()).as_str(),
+                ))
+                .with_child_opt(
+                    underlying
+                        .is_some()
+                        .then_some(Html::span(&["space-before", "space-after"], ":")),
+                )
+                .with_child_opt(underlying.map(|ty| fmt_type(&ty, builder)))
+                .with_child(Html::span(&["space-before"], "{ ... }")),
+        )
+        .into()
+}
+
+pub fn fmt_enumerator_value(enumerator: &Entity) -> Option<String> {
+    let (signed, unsigned) = enumerator.get_enum_constant_value()?;
+    // The constant value is reported both ways, so pick the one matching the
+    // signedness of the underlying type
+    let is_unsigned = enumerator
+        .get_semantic_parent()
+        .and_then(|parent| parent.get_enum_underlying_type())
+        .is_some_and(|ty| ty.get_canonical_type().is_unsigned_integer());
+    Some(if is_unsigned {
+        unsigned.to_string()
+    } else {
+        signed.to_string()
+    })
+}
+
+pub fn fmt_enumerator(enumerator: &Entity, builder: &Builder) -> Html {
+    HtmlElement::new("details")
+        .with_class("entity-desc")
+        .with_attr_opt("id", enumerator.get_name())
+        .with_child(
+            HtmlElement::new("summary")
+                .with_classes(&["entity", "var"])
+                .with_child(Html::span(
+                    &["name"],
+                    &enumerator.get_name().unwrap_or("_anon".into()),
+                ))
+                .with_child_opt(fmt_enumerator_value(enumerator).map(|value| {
+                    HtmlList::new(vec![
+                        Html::span(&["space-before"], "="),
+                        Html::span(&["space-before", "literal"], &value),
+                    ])
+                }))
+                .with_child(HtmlText::new(",")),
+        )
+        .with_child(
+            HtmlElement::new("div").with_child(
+                enumerator
+                    .get_comment()
+                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                    .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+            ),
+        )
+        .into()
+}
+
+pub fn get_aliased_type<'e>(alias: &Entity<'e>) -> Option<Type<'e>> {
+    // Alias templates keep the actual alias declaration as a child
+    if alias.get_kind() == EntityKind::TypeAliasTemplateDecl {
+        alias
+            .get_children()
+            .into_iter()
+            .find(|child| child.get_kind() == EntityKind::TypeAliasDecl)?
+            .get_typedef_underlying_type()
+    } else {
+        alias.get_typedef_underlying_type()
+    }
+}
+
+pub fn fmt_alias_decl<'e, T: ASTEntry<'e>>(entry: &T, builder: &Builder) -> Html {
+    HtmlElement::new("div")
+        .with_classes(&["entity", "alias"])
+        .with_child_opt(fmt_template_args(entry.entity(), builder))
+        .with_child(Html::span(&["keyword", "space-after"], "using"))
+        .with_child(Html::span(
+            &["name", "alias"],
+            entry.entity().get_name().unwrap_or("_".into()).as_str(),
+        ))
+        .with_child(Html::span(&["space-before", "space-after"], "="))
+        .with_child(
+            get_aliased_type(entry.entity())
+                .map(|ty| fmt_type(&ty, builder))
+                .unwrap_or(Html::span(&["template-param"], "_unk")),
+        )
+        .with_child(HtmlText::new(";"))
+        .into()
+}
+
+pub fn get_variable_specifiers(var: &Entity) -> Vec<String> {
+    // Clang doesn't expose most of these directly, so look at the tokens
+    // that come before the variable's name
+    let Some(name) = var.get_name() else {
+        return Vec::new();
+    };
+    var.get_range()
+        .map(|range| {
+            range
+                .tokenize()
+                .into_iter()
+                .map(|token| token.get_spelling())
+                .take_while(|spelling| *spelling != name)
+                .filter(|spelling| {
+                    matches!(
+                        spelling.as_str(),
+                        "static" | "inline" | "constexpr" | "constinit" | "extern" | "thread_local"
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn get_variable_initializer(var: &Entity) -> Option<String> {
+    // Only show initializers that are simple literals, anything more complex
+    // is an implementation detail
+    let mut expr = var.get_children().into_iter().last()?;
+    while matches!(expr.get_kind(), EntityKind::UnexposedExpr | EntityKind::ParenExpr) {
+        let [child] = expr.get_children()[..] else {
+            return None;
+        };
+        expr = child;
+    }
+    let is_literal = |e: &Entity| {
+        matches!(
+            e.get_kind(),
+            EntityKind::IntegerLiteral
+                | EntityKind::FloatingLiteral
+                | EntityKind::StringLiteral
+                | EntityKind::CharacterLiteral
+                | EntityKind::BoolLiteralExpr
+                | EntityKind::NullPtrLiteralExpr
+        )
+    };
+    // Keep unary operators around for things like negative numbers
+    let shown = is_literal(&expr)
+        || (expr.get_kind() == EntityKind::UnaryOperator
+            && matches!(expr.get_children()[..], [child] if is_literal(&child)));
+    shown.then(|| expr.extract_source_string_cleaned()).flatten()
+}
+
+pub fn fmt_variable(var: &Entity, builder: &Builder) -> Html {
+    HtmlElement::new("details")
+        .with_class("entity-desc")
+        .with_attr_opt("id", var.get_name())
+        .with_child(
+            HtmlElement::new("summary")
+                .with_classes(&["entity", "var"])
+                .with_children(
+                    get_variable_specifiers(var)
+                        .iter()
+                        .map(|kw| Html::span(&["keyword", "space-after"], kw))
+                        .collect(),
+                )
+                .with_child_opt(var.get_type().map(|t| fmt_type(&t, builder)))
+                .with_child(Html::span(
+                    &["name", "space-before"],
+                    &var.get_name().unwrap_or("_anon".into()),
+                ))
+                .with_child_opt(get_variable_initializer(var).map(|value| {
+                    HtmlList::new(vec![
+                        Html::span(&["space-before"], "="),
+                        Html::span(&["space-before", "literal"], &value),
+                    ])
+                }))
+                .with_child(HtmlText::new(";")),
+        )
+        .with_child(
+            HtmlElement::new("div").with_child(
+                var.get_comment()
+                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                    .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+            ),
+        )
+        .into()
+}
+
+pub fn get_macro_params_and_body(mac: &Entity) -> Option<(Option<Vec<String>>, Option<String>)> {
+    let range = mac.get_range()?;
+    let mut tokens = range.tokenize().into_iter().skip(1).peekable();
+
+    // Function-like macros have their parameter list right after the name
+    let params = mac.is_function_like_macro().then(|| {
+        tokens
+            .by_ref()
+            .skip(1)
+            .map(|token| token.get_spelling())
+            .take_while(|spelling| spelling != ")")
+            .filter(|spelling| spelling != ",")
+            .collect()
+    });
+
+    // Use the source text for the body so the original spacing is kept
+    let body = tokens.peek().and_then(|first| {
+        let start = first.get_range().get_start().get_file_location();
+        let end = range.get_end().get_file_location();
+        let body = start.file?.get_contents()?;
+        Some(
+            body.get(start.offset as usize..end.offset as usize)?
+                .replace("\\\n", " ")
+                .split_whitespace()
+                .intersperse(" ")
+                .collect(),
+        )
+    });
+
+    Some((params, body))
+}
+
+fn fmt_macro_decl(mac: &Entity) -> Html {
+    let (params, body) = get_macro_params_and_body(mac).unwrap_or((None, None));
+    HtmlElement::new("span")
+        .with_class("macro-decl")
+        .with_child(Html::span(&["keyword", "space-after"], "#define"))
+        .with_child(Html::span(&["name"], &mac.get_name().unwrap_or("_anon".into())))
+        .with_child_opt(params.map(|params| {
+            HtmlElement::new("span").with_class("params").with_children(
+                params
+                    .iter()
+                    .map(|param| Html::span(&["template-param"], param))
+                    .collect::<Vec<_>>()
+                    .insert_between(|| Html::span(&["comma", "space-after"], ","))
+                    .surround(HtmlText::new("(").into(), HtmlText::new(")").into()),
+            )
+        }))
+        .with_child_opt(body.map(|body| Html::span(&["space-before", "literal"], &body)))
+        .into()
+}
+
+pub fn fmt_macro(mac: &Entity, builder: &Builder) -> Html {
+    HtmlElement::new("details")
+        .with_class("entity-desc")
+        .with_attr_opt("id", mac.get_name())
+        .with_child(
+            HtmlElement::new("summary")
+                .with_classes(&["entity", "macro"])
+                .with_child(fmt_macro_decl(mac)),
+        )
+        .with_child(
+            HtmlElement::new("div").with_child(
+                mac.get_doc_comment()
+                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                    .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+            ),
+        )
+        .into()
+}
+
+pub fn fmt_namespace_entry<'e, T: ASTEntry<'e>>(entry: &T, builder: &Builder) -> Html {
+    HtmlElement::new("div")
+        .with_class("namespace-entry")
+        .with_child(
+            HtmlElement::new("a")
+                .with_attr("href", entry.url().to_absolute(builder.config.clone()))
+                .with_child(
+                    HtmlElement::new("code")
+                        .with_class(entry.category())
+                        .with_child(HtmlText::new(entry.name())),
+                ),
+        )
+        .with_child(
+            entry
+                .entity()
+                .get_doc_comment()
+                .and_then(|s| JSDocComment::parse(s, builder).brief())
+                .map(|brief| {
+                    fmt_markdown(builder, &fmt_autolinks(builder, &brief), None::<fn(_) -> _>)
+                })
+                .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+        )
+        .into()
+}
+
 pub fn output_entity<'e, T: ASTEntry<'e>>(
     entry: &T,
     builder: &Builder,
@@ -443,7 +944,7 @@ pub fn output_entity<'e, T: ASTEntry<'e>>(
             "description",
             entry
                 .entity()
-                .get_comment()
+                .get_doc_comment()
                 .map(|s| JSDocComment::parse(s, builder).to_html(false))
                 .unwrap_or(Html::span(&["no-desc"], "No description provided")),
         ),
@@ -457,7 +958,7 @@ pub fn output_entity<'e, T: ASTEntry<'e>>(
                 "Examples",
                 entry
                     .entity()
-                    .get_comment()
+                    .get_doc_comment()
                     .map(|s| {
                         JSDocComment::parse(s, builder)
                             .examples()
@@ -518,6 +1019,22 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
                     .collect::<Vec<_>>(),
             ),
         ),
+        (
+            "public_static_members",
+            fmt_section(
+                "Public static members",
+                entry
+                    .entity()
+                    .get_children()
+                    .iter()
+                    .filter(|child| {
+                        child.get_kind() == EntityKind::VarDecl
+                            && child.get_accessibility() == Some(Accessibility::Public)
+                    })
+                    .map(|e| fmt_variable(e, builder))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
         (
             "public_members",
             fmt_section(
@@ -534,6 +1051,22 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
                     .collect::<Vec<_>>(),
             ),
         ),
+        (
+            "protected_static_members",
+            fmt_section(
+                "Protected static members",
+                entry
+                    .entity()
+                    .get_children()
+                    .iter()
+                    .filter(|child| {
+                        child.get_kind() == EntityKind::VarDecl
+                            && child.get_accessibility() == Some(Accessibility::Protected)
+                    })
+                    .map(|e| fmt_variable(e, builder))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
         (
             "protected_members",
             fmt_section(
@@ -550,76 +1083,230 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
                     .collect::<Vec<_>>(),
             ),
         ),
+        (
+            "inherited_members",
+            HtmlList::new(
+                get_inherited_members(entry.entity())
+                    .iter()
+                    .map(|inherited| fmt_inherited_members(inherited, builder))
+                    .collect(),
+            )
+            .into(),
+        ),
+        (
+            "inheritance_graph",
+            builder
+                .hierarchy
+                .svg(entry.entity(), builder)
+                .map(|svg| {
+                    fmt_section(
+                        "Inheritance",
+                        vec![
+                            svg,
+                            HtmlElement::new("p")
+                                .with_child(
+                                    HtmlElement::new("a")
+                                        .with_attr(
+                                            "href",
+                                            HierarchyPage.url().to_absolute(builder.config.clone()),
+                                        )
+                                        .with_text("See the full class hierarchy"),
+                                )
+                                .into(),
+                        ],
+                    )
+                })
+                .unwrap_or(HtmlList::new(Vec::new()).into()),
+        ),
         (
             "derived_classes",
-            fmt_section("Derived classes", {
-                let mut derived: Vec<_> = get_all_classes(&builder.root)
-                    .into_iter()
-                    .filter(|potentially_derived| {
-                        potentially_derived
-                            .entity()
-                            .get_children()
-                            .iter()
-                            .any(|child| {
-                                child.get_kind() == EntityKind::BaseSpecifier
-                                    && child
-                                        .get_type()
-                                        .and_then(|t| t.get_declaration())
-                                        .map(|decl| decl.get_usr() == entry.entity().get_usr())
-                                        .unwrap_or(false)
-                            })
-                    })
-                    .collect();
-                derived.sort_by_key(|d| d.entity().get_name().unwrap_or("_".into()));
-                derived
-                    .into_iter()
-                    .map(|d| fmt_derived_class(d.entity(), builder))
-                    .collect::<Vec<_>>()
-            }),
+            fmt_section(
+                "Derived classes",
+                builder
+                    .hierarchy
+                    .derived(entry.entity())
+                    .iter()
+                    .map(|d| fmt_derived_class(d, builder))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+    ]);
+    ent
+}
+
+pub fn output_function<'e>(entry: &Function<'e>, builder: &Builder) -> Vec<(&'static str, Html)> {
+    let mut ent = output_entity(entry, builder);
+    if let [fun] = entry.overloads() {
+        ent.extend(vec![
+            (
+                "function_signature",
+                HtmlElement::new("div")
+                    .with_attr_opt("id", member_fun_link(fun))
+                    .with_child(fmt_fun_signature(fun, builder))
+                    .into(),
+            ),
+            ("overloads", Html::Raw(String::new())),
+        ]);
+    } else {
+        // Every overload gets its own signature, description and examples
+        ent.retain(|(key, _)| !matches!(*key, "description" | "examples"));
+        ent.extend(vec![
+            (
+                "description",
+                Html::p(format!(
+                    "{} has {} overloads",
+                    entry.name(),
+                    entry.overloads().len()
+                )),
+            ),
+            ("examples", Html::Raw(String::new())),
+            ("function_signature", Html::Raw(String::new())),
+            (
+                "overloads",
+                fmt_section(
+                    "Overloads",
+                    entry
+                        .overloads()
+                        .iter()
+                        .map(|fun| fmt_class_method(fun, builder))
+                        .collect(),
+                ),
+            ),
+        ]);
+    }
+    ent
+}
+
+pub fn output_enum<'e, T: ASTEntry<'e>>(
+    entry: &T,
+    builder: &Builder,
+) -> Vec<(&'static str, Html)> {
+    let mut ent = output_entity(entry, builder);
+    ent.extend(vec![
+        ("enum_decl", fmt_enum_decl(entry, builder)),
+        (
+            "enumerators",
+            fmt_section(
+                "Enumerators",
+                entry
+                    .entity()
+                    .get_children()
+                    .iter()
+                    .filter(|child| child.get_kind() == EntityKind::EnumConstantDecl)
+                    .map(|e| fmt_enumerator(e, builder))
+                    .collect::<Vec<_>>(),
+            ),
         ),
     ]);
     ent
 }
 
-pub fn output_function<'e, T: ASTEntry<'e>>(
+pub fn output_alias<'e, T: ASTEntry<'e>>(
+    entry: &T,
+    builder: &Builder,
+) -> Vec<(&'static str, Html)> {
+    let mut ent = output_entity(entry, builder);
+    ent.extend(vec![("alias_decl", fmt_alias_decl(entry, builder))]);
+    ent
+}
+
+pub fn output_variable<'e, T: ASTEntry<'e>>(
+    entry: &T,
+    builder: &Builder,
+) -> Vec<(&'static str, Html)> {
+    let mut ent = output_entity(entry, builder);
+    ent.extend(vec![("variable_decl", fmt_variable(entry.entity(), builder))]);
+    ent
+}
+
+pub fn output_macro<'e, T: ASTEntry<'e>>(
     entry: &T,
     builder: &Builder,
 ) -> Vec<(&'static str, Html)> {
     let mut ent = output_entity(entry, builder);
     ent.extend(vec![(
-        "function_signature",
-        fmt_fun_signature(entry.entity(), builder),
+        "macro_decl",
+        HtmlElement::new("div")
+            .with_classes(&["entity", "macro"])
+            .with_child(fmt_macro_decl(entry.entity()))
+            .into(),
     )]);
     ent
 }
 
+pub fn output_namespace<'e>(ns: &Namespace<'e>, builder: &Builder) -> Vec<(&'static str, Html)> {
+    let fmt_entries = |title: &str, category: &str| {
+        let mut entries = ns
+            .entries
+            .values()
+            .filter(|entry| entry.category() == category)
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.name());
+        fmt_section(
+            title,
+            entries
+                .into_iter()
+                .map(|entry| fmt_namespace_entry(entry, builder))
+                .collect(),
+        )
+    };
+
+    let mut ent = output_entity(ns, builder);
+    ent.extend(vec![
+        ("namespaces", fmt_entries("Namespaces", "namespace")),
+        ("classes", fmt_entries("Classes", "class")),
+        ("structs", fmt_entries("Structs", "struct")),
+        ("enums", fmt_entries("Enums", "enum")),
+        ("aliases", fmt_entries("Type aliases", "alias")),
+        ("functions", fmt_entries("Functions", "function")),
+        ("variables", fmt_entries("Variables", "variable")),
+        ("macros", fmt_entries("Macros", "macro")),
+    ]);
+    ent
+}
+
 fn fmt_autolinks_recursive(
     entity: &CppItem,
-    config: Arc<Config>,
+    link: &dyn Fn(&Entity) -> Option<String>,
     annotations: &mut Annotations<'_>,
 ) {
     annotations.rewind();
     while let Some(word) = annotations.next() {
         // skip stuff that have all-lowercase names (so words like "get"
         // and "data" don't get autolinked)
-        if !word.chars().all(|c| c.is_lowercase()) && *word == entity.name() {
-            if let Some(url) = entity.entity().abs_docs_url(config.clone()) {
-                annotations.annotate(format!("[{word}]({})", url));
-            }
+        if !word.chars().all(|c| c.is_lowercase())
+            && *word == entity.name()
+            && let Some(url) = link(entity.entity())
+        {
+            annotations.annotate(format!("[{word}]({url})"));
         }
     }
 
     if let CppItem::Namespace(ns) = entity {
         for v in ns.entries.values() {
-            fmt_autolinks_recursive(v, config.clone(), annotations);
+            fmt_autolinks_recursive(v, link, annotations);
         }
     }
 }
 
 pub fn fmt_autolinks(builder: &Builder, text: &str) -> String {
+    fmt_autolinks_with(builder, text, &|entity| {
+        entity
+            .abs_docs_url(builder.config.clone())
+            .map(|url| url.to_string())
+    })
+}
+
+/// Turn the names of documented entities in a text into markdown links,
+/// using `link` to get the link for each entity
+pub fn fmt_autolinks_with(
+    builder: &Builder,
+    text: &str,
+    link: &dyn Fn(&Entity) -> Option<String>,
+) -> String {
     let mut annotations = Annotations::new(text);
     for entry in builder.root.entries.values() {
-        fmt_autolinks_recursive(entry, builder.config.clone(), &mut annotations);
+        fmt_autolinks_recursive(entry, link, &mut annotations);
     }
     annotations.into_result()
 }
@@ -666,6 +1353,29 @@ pub fn fmt_emoji(text: &CowStr) -> String {
     res
 }
 
+/// Get the anchor for a function on its page. This is derived from the
+/// function's signature so that overloads each get their own stable anchor,
+/// like `getChildByID-const-std-string-ref` for `getChildByID(std::string const&)`
+/// (clang spells the parameter type as `const std::string &`)
 pub fn member_fun_link(entity: &Entity) -> Option<String> {
-    entity.get_name()
+    let mut parts = vec![entity.get_name()?];
+    for arg in entity.get_function_arguments().unwrap_or_default() {
+        let ty = arg
+            .get_type()
+            .map(|t| t.get_display_name())
+            .unwrap_or("_".into())
+            .replace("&&", " rref ")
+            .replace('&', " ref ")
+            .replace('*', " ptr ")
+            .replace("...", " variadic ");
+        parts.extend(
+            ty.split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|p| !p.is_empty())
+                .map(|p| p.to_owned()),
+        );
+    }
+    if entity.is_const_method() {
+        parts.push("const".into());
+    }
+    Some(parts.join("-"))
 }
@@ -0,0 +1,172 @@
+use super::{
+    shared::{
+        fmt_enumerator_value, get_aliased_type, get_macro_params_and_body,
+        get_variable_initializer, get_variable_specifiers,
+    },
+    traits::EntityMethods,
+};
+use clang::{Accessibility, Entity, EntityKind, Type};
+
+// Plain text versions of the declarations the HTML pages show, for the
+// output formats that aren't HTML
+
+fn type_name(ty: Option<Type>) -> String {
+    ty.map(|t| t.get_display_name()).unwrap_or("_".into())
+}
+
+fn access_keyword(access: Accessibility) -> &'static str {
+    match access {
+        Accessibility::Public => "public",
+        Accessibility::Protected => "protected",
+        Accessibility::Private => "private",
+    }
+}
+
+/// The `template <...>` line of a templated entity
+pub fn template_decl(entity: &Entity) -> Option<String> {
+    let params = entity
+        .get_children()
+        .into_iter()
+        .filter(|e| e.get_kind() == EntityKind::TemplateTypeParameter)
+        .map(|e| {
+            e.extract_source_string_cleaned()
+                .or_else(|| e.get_name().map(|x| format!("typename {x}")))
+                .unwrap_or("_".into())
+        })
+        .collect::<Vec<_>>();
+    (!params.is_empty()).then(|| format!("template <{}>", params.join(", ")))
+}
+
+fn with_template(entity: &Entity, decl: String) -> String {
+    match template_decl(entity) {
+        Some(template) => format!("{template}\n{decl}"),
+        None => decl,
+    }
+}
+
+pub fn fun_signature(fun: &Entity) -> String {
+    let mut sig = String::new();
+    if fun.is_static_method() {
+        sig += "static ";
+    }
+    if fun.is_virtual_method() {
+        sig += "virtual ";
+    }
+    if let Some(ret) = fun.get_result_type() {
+        sig += &ret.get_display_name();
+        sig.push(' ');
+    }
+    sig += &fun.get_name().unwrap_or("_anon".into());
+    sig += &format!(
+        "({})",
+        fun.get_function_arguments()
+            .unwrap_or_default()
+            .iter()
+            .map(|arg| match arg.get_display_name() {
+                Some(name) => format!("{} {name}", type_name(arg.get_type())),
+                None => type_name(arg.get_type()),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if fun.is_const_method() {
+        sig += " const";
+    }
+    if fun.is_override() {
+        sig += " override";
+    }
+    if fun.is_final() {
+        sig += " final";
+    }
+    if fun.is_pure_virtual_method() {
+        sig += " = 0";
+    }
+    with_template(fun, sig)
+}
+
+pub fn classlike_decl(class: &Entity, keyword: &str) -> String {
+    let bases = class
+        .get_children()
+        .into_iter()
+        .filter(|p| p.get_kind() == EntityKind::BaseSpecifier)
+        .map(|base| {
+            [
+                base.get_accessibility().map(access_keyword),
+                base.is_virtual_base().then_some("virtual"),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|kw| kw.to_owned())
+            .chain([type_name(base.get_type())])
+            .collect::<Vec<_>>()
+            .join(" ")
+        })
+        .collect::<Vec<_>>();
+
+    let mut decl = format!("{keyword} {}", class.get_name().unwrap_or("_".into()));
+    if !bases.is_empty() {
+        decl += &format!(" : {}", bases.join(", "));
+    }
+    with_template(class, decl + " { ... }")
+}
+
+pub fn enum_decl(en: &Entity) -> String {
+    let mut decl = String::from("enum ");
+    if en.is_scoped() {
+        decl += "class ";
+    }
+    decl += &en.get_name().unwrap_or("_".into());
+    if let Some(ty) = en.get_enum_underlying_type() {
+        decl += &format!(" : {}", ty.get_display_name());
+    }
+    decl + " { ... }"
+}
+
+pub fn enumerator_decl(enumerator: &Entity) -> String {
+    let name = enumerator.get_name().unwrap_or("_anon".into());
+    match fmt_enumerator_value(enumerator) {
+        Some(value) => format!("{name} = {value},"),
+        None => format!("{name},"),
+    }
+}
+
+pub fn alias_decl(alias: &Entity) -> String {
+    with_template(
+        alias,
+        format!(
+            "using {} = {};",
+            alias.get_name().unwrap_or("_".into()),
+            get_aliased_type(alias)
+                .map(|ty| ty.get_display_name())
+                .unwrap_or("_unk".into())
+        ),
+    )
+}
+
+/// Declaration of a variable, static member or field
+pub fn variable_decl(var: &Entity) -> String {
+    let mut decl = get_variable_specifiers(var)
+        .into_iter()
+        .chain([
+            type_name(var.get_type()),
+            var.get_name().unwrap_or("_anon".into()),
+        ])
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Some(value) = get_variable_initializer(var) {
+        decl += &format!(" = {value}");
+    }
+    decl + ";"
+}
+
+pub fn macro_decl(mac: &Entity) -> String {
+    let (params, body) = get_macro_params_and_body(mac).unwrap_or((None, None));
+    let mut decl = format!("#define {}", mac.get_name().unwrap_or("_anon".into()));
+    if let Some(params) = params {
+        decl += &format!("({})", params.join(", "));
+    }
+    if let Some(body) = body {
+        decl += &format!(" {body}");
+    }
+    decl
+}
@@ -1,17 +1,17 @@
 use clang::{Accessibility, Entity, EntityKind};
 use serde_json::json;
 
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc};
 
 use tokio::task::JoinHandle;
 
 use crate::{
     config::{Config, ExternalLib, Source},
-    html::Html,
+    html::{Html, template::Template},
     url::UrlPath,
 };
 
-use super::{builder::Builder, namespace::CppItemKind};
+use super::{builder::Builder, namespace::CppItemKind, shared::member_fun_link};
 
 pub trait EntityMethods<'e> {
     /// Get the config source for this entity
@@ -45,6 +45,12 @@ pub trait EntityMethods<'e> {
     fn get_member_functions(&self, visibility: Access, include_statics: Include)
         -> Vec<Entity<'e>>;
 
+    /// Checks if this is a function declared inside a class-like entity
+    fn is_member_function(&self) -> bool;
+
+    /// Checks if this is a field or static variable declared inside a class-like entity
+    fn is_member_variable(&self) -> bool;
+
     /// Gets the function arguments for this method, including templated ones
     fn get_function_arguments(&self) -> Option<Vec<Entity<'e>>>;
 
@@ -55,6 +61,20 @@ pub trait EntityMethods<'e> {
     /// Same as extract_source_string, but removes new lines, double spaces, and leading/trailing whitespace
     fn extract_source_string_cleaned(&self) -> Option<String>;
 
+    /// Gets the raw doc comment for this entity. Unlike get_comment, this
+    /// also finds the comment preceding a macro definition, and overrides
+    /// without a comment of their own get the one of the method they override
+    fn get_doc_comment(&self) -> Option<String>;
+
+    /// Checks if this method is marked `override`
+    fn is_override(&self) -> bool;
+
+    /// Checks if this method or class is marked `final`
+    fn is_final(&self) -> bool;
+
+    /// Checks if this is a macro that only exists as an include guard
+    fn is_include_guard(&self) -> bool;
+
     /// Checks if the entitiy is in one of the allowed external libraries
     fn get_allowed_external_lib(&self, config: Arc<Config>) -> Option<Arc<ExternalLib>>;
 }
@@ -106,6 +126,30 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
                 self.get_name()?
             ))
             .ok()
+        }
+        // Member functions live on the page of their class
+        else if self.is_member_function() {
+            Some(
+                self.get_semantic_parent()?
+                    .abs_docs_url(config)?
+                    .with_fragment(member_fun_link(self)?),
+            )
+        }
+        // Same goes for fields and static members
+        else if self.is_member_variable() {
+            Some(
+                self.get_semantic_parent()?
+                    .abs_docs_url(config)?
+                    .with_fragment(self.get_name()?),
+            )
+        }
+        // Free functions get a fragment pointing to the right overload
+        else if let Some(CppItemKind::Function) = CppItemKind::from(self) {
+            Some(
+                self.rel_docs_url()?
+                    .to_absolute(config)
+                    .with_fragment(member_fun_link(self)?),
+            )
         } else {
             Some(self.rel_docs_url()?.to_absolute(config))
         }
@@ -206,6 +250,38 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
             .collect()
     }
 
+    fn is_member_function(&self) -> bool {
+        matches!(
+            self.get_kind(),
+            EntityKind::Method
+                | EntityKind::Constructor
+                | EntityKind::Destructor
+                | EntityKind::ConversionFunction
+                | EntityKind::FunctionTemplate
+        ) && self.get_semantic_parent().is_some_and(|p| {
+            matches!(
+                p.get_kind(),
+                EntityKind::ClassDecl
+                    | EntityKind::StructDecl
+                    | EntityKind::ClassTemplate
+                    | EntityKind::ClassTemplatePartialSpecialization
+            )
+        })
+    }
+
+    fn is_member_variable(&self) -> bool {
+        matches!(self.get_kind(), EntityKind::VarDecl | EntityKind::FieldDecl)
+            && self.get_semantic_parent().is_some_and(|p| {
+                matches!(
+                    p.get_kind(),
+                    EntityKind::ClassDecl
+                        | EntityKind::StructDecl
+                        | EntityKind::ClassTemplate
+                        | EntityKind::ClassTemplatePartialSpecialization
+                )
+            })
+    }
+
     fn get_function_arguments(&self) -> Option<Vec<Entity<'e>>> {
         if !matches!(
             self.get_kind(),
@@ -247,6 +323,51 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
         )
     }
 
+    fn get_doc_comment(&self) -> Option<String> {
+        if self.get_kind() != EntityKind::MacroDefinition {
+            return self.get_comment().or_else(|| {
+                self.get_overridden_methods()?
+                    .iter()
+                    .find_map(|base| base.get_doc_comment())
+            });
+        }
+
+        // Clang doesn't attach comments to macros, so look for a /** */
+        // block right before the #define
+        let start = self.get_range()?.get_start().get_file_location();
+        let contents = start.file?.get_contents()?;
+        let before = contents.get(..start.offset as usize)?.trim_end();
+        let before = before.strip_suffix("define").unwrap_or(before).trim_end();
+        let before = before.strip_suffix('#').unwrap_or(before).trim_end();
+        let body = before.strip_suffix("*/")?;
+        let comment_start = body.rfind("/**")?;
+        (!body[comment_start..].contains("*/")).then(|| before[comment_start..].into())
+    }
+
+    fn is_override(&self) -> bool {
+        self.get_children()
+            .iter()
+            .any(|child| child.get_kind() == EntityKind::OverrideAttr)
+    }
+
+    fn is_final(&self) -> bool {
+        self.get_children()
+            .iter()
+            .any(|child| child.get_kind() == EntityKind::FinalAttr)
+    }
+
+    fn is_include_guard(&self) -> bool {
+        self.get_kind() == EntityKind::MacroDefinition
+            && !self.is_function_like_macro()
+            // Only the name token means the macro has no body
+            && self.get_range().is_some_and(|range| range.tokenize().len() == 1)
+            && self.get_name().is_some_and(|name| {
+                ["_H", "_H_", "_HPP", "_HPP_", "_INCLUDED"]
+                    .iter()
+                    .any(|suffix| name.ends_with(suffix))
+            })
+    }
+
     fn get_allowed_external_lib(&self, config: Arc<Config>) -> Option<Arc<ExternalLib>> {
         self.is_in_system_header()
             .then(|| self.get_location())
@@ -264,51 +385,15 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
     }
 }
 
-#[derive(Clone)]
-pub struct SubItem {
-    pub title: String,
-}
-
-impl SubItem {
-    pub fn for_classlike(entity: &Entity) -> Vec<SubItem> {
-        let Some(kind) = CppItemKind::from(entity) else {
-            return Vec::new();
-        };
-        match kind {
-            CppItemKind::Class | CppItemKind::Struct => entity
-                .get_member_functions(Access::All, Include::All)
-                .into_iter()
-                .filter_map(|e| {
-                    Some(SubItem {
-                        title: e.get_name()?,
-                    })
-                })
-                .collect(),
-
-            CppItemKind::Namespace | CppItemKind::Function => Vec::new(),
-        }
-    }
-}
-
 pub enum NavItem {
     Root(Option<String>, Vec<NavItem>),
     Dir(String, Vec<NavItem>, Option<(String, bool)>, bool),
-    Link(String, UrlPath, Option<(String, bool)>, Vec<SubItem>),
+    Link(String, UrlPath, Option<(String, bool)>),
 }
 
 impl NavItem {
-    pub fn new_link(
-        name: &str,
-        url: UrlPath,
-        icon: Option<(&str, bool)>,
-        suboptions: Vec<SubItem>,
-    ) -> NavItem {
-        NavItem::Link(
-            name.into(),
-            url,
-            icon.map(|s| (s.0.into(), s.1)),
-            suboptions,
-        )
+    pub fn new_link(name: &str, url: UrlPath, icon: Option<(&str, bool)>) -> NavItem {
+        NavItem::Link(name.into(), url, icon.map(|s| (s.0.into(), s.1)))
     }
 
     pub fn new_dir(name: &str, items: Vec<NavItem>, icon: Option<(&str, bool)>) -> NavItem {
@@ -328,36 +413,9 @@ impl NavItem {
         NavItem::Root(name.map(|s| s.into()), items)
     }
 
-    pub fn suboptions_titles(&self, config: Arc<Config>) -> HashMap<String, usize> {
-        match self {
-            NavItem::Link(name, _, _, suboptions) => {
-                let mut res = HashMap::new();
-                for opt in suboptions.iter().map(|o| format!("{}::{}", name, o.title)) {
-                    if let Some(r) = res.get_mut(&opt) {
-                        *r += 1;
-                    } else {
-                        res.insert(opt, 0);
-                    }
-                }
-                res
-            }
-
-            NavItem::Dir(name, items, _, _) => items
-                .iter()
-                .flat_map(|i| i.suboptions_titles(config.clone()))
-                .map(|(t, count)| (format!("{}::{}", name, t), count))
-                .collect(),
-
-            NavItem::Root(_, items) => items
-                .iter()
-                .flat_map(|i| i.suboptions_titles(config.clone()))
-                .collect(),
-        }
-    }
-
     pub fn to_json(&self, config: Arc<Config>) -> serde_json::Value {
         match self {
-            NavItem::Link(name, url, icon, _) => {
+            NavItem::Link(name, url, icon) => {
                 json!({
                     "type": "link",
                     "icon": icon,
@@ -397,12 +455,30 @@ pub trait Entry<'e> {
 }
 
 pub trait OutputEntry<'e>: Entry<'e> {
-    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>);
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>);
+    /// The page for the markdown output format
+    fn markdown(&self, builder: &'e Builder<'e>) -> String;
+    /// Structured data about the page for templates using the Jinja engine,
+    /// in addition to the pre-rendered HTML they always get
+    fn context(&self, _builder: &'e Builder<'e>) -> Option<serde_json::Value> {
+        None
+    }
     fn description(&self, builder: &'e Builder<'e>) -> String;
+    /// Hash of everything this entry's page is built from. If it matches the
+    /// previous build, the page is not regenerated. `None` means the page is
+    /// always rebuilt
+    fn cache_key(&self, _builder: &'e Builder<'e>) -> Option<u64> {
+        None
+    }
 }
 
 pub trait ASTEntry<'e>: Entry<'e> {
     fn entity(&self) -> &Entity<'e>;
+    /// All the declarations documented by this entry. Only differs from
+    /// `entity` for overload sets
+    fn entities(&self) -> Vec<Entity<'e>> {
+        vec![*self.entity()]
+    }
     fn category(&self) -> &'static str;
     fn output_description(&self, builder: &'e Builder<'e>) -> String {
         format!(
@@ -2,14 +2,16 @@ use log::info;
 
 use crate::{
     config::Config,
-    html::{Html, HtmlElement},
+    html::{Html, HtmlElement, template::Template},
     url::UrlPath,
 };
 use std::{cmp::Ordering, collections::HashMap, ffi::OsStr, fs, path::PathBuf, sync::Arc};
 
 use super::{
     builder::Builder,
+    cache::hash_of,
     markdown::{extract_metadata_from_md, output_tutorial, Metadata},
+    markdown_output::markdown_tutorial,
     shared::fmt_section,
     traits::{BuildResult, Entry, NavItem, OutputEntry},
 };
@@ -40,6 +42,10 @@ impl Tutorial {
             path,
         }
     }
+
+    pub fn content(&self) -> &str {
+        &self.unparsed_content
+    }
 }
 
 impl<'e> Entry<'e> for Tutorial {
@@ -66,13 +72,12 @@ impl<'e> Entry<'e> for Tutorial {
                     .map(|i| (i.as_str(), false))
                     .unwrap_or(("bookmark", false)),
             ),
-            Vec::new(),
         )
     }
 }
 
 impl<'e> OutputEntry<'e> for Tutorial {
-    fn output(&self, builder: &Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+    fn output(&self, builder: &Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
         (
             builder.config.templates.tutorial.clone(),
             output_tutorial(
@@ -84,12 +89,20 @@ impl<'e> OutputEntry<'e> for Tutorial {
         )
     }
 
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        markdown_tutorial(builder, self.url(), &self.name(), &self.unparsed_content).finish()
+    }
+
     fn description(&self, builder: &'e Builder<'e>) -> String {
         self.metadata
             .description
             .clone()
             .unwrap_or(format!("Tutorial for {}", builder.config.project.name))
     }
+
+    fn cache_key(&self, _builder: &'e Builder<'e>) -> Option<u64> {
+        Some(hash_of(&self.unparsed_content))
+    }
 }
 
 pub struct TutorialFolder {
@@ -200,6 +213,10 @@ impl TutorialFolder {
         }
     }
 
+    pub fn index(&self) -> Option<&str> {
+        self.index.as_deref()
+    }
+
     pub fn folders_sorted(&self) -> Vec<&TutorialFolder> {
         let mut vec = self.folders.iter().collect::<Vec<_>>();
         vec.sort_unstable_by(|a, b| {
@@ -285,7 +302,7 @@ impl<'e> Entry<'e> for TutorialFolder {
 }
 
 impl<'e> OutputEntry<'e> for TutorialFolder {
-    fn output(&self, builder: &Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+    fn output(&self, builder: &Builder<'e>) -> (Arc<Template>, Vec<(&'static str, Html)>) {
         (
             if self.index.is_some() {
                 builder.config.templates.tutorial.clone()
@@ -316,6 +333,29 @@ impl<'e> OutputEntry<'e> for TutorialFolder {
         )
     }
 
+    fn markdown(&self, builder: &'e Builder<'e>) -> String {
+        let title = if self.is_root {
+            builder.config.project.name.clone()
+        } else {
+            self.name()
+        };
+        let mut page = markdown_tutorial(
+            builder,
+            self.url(),
+            &title,
+            self.index.as_deref().unwrap_or(""),
+        );
+        page.section("Pages", self.tutorials_sorted(), |page, tut| {
+            let link = format!("[{}]({})", tut.name(), page.link(&tut.url()));
+            page.list_item(&link);
+        });
+        // There's no navbar, so the root links to the API as well
+        if self.is_root {
+            page.namespace_entries(&builder.root);
+        }
+        page.finish()
+    }
+
     fn description(&self, builder: &'e Builder<'e>) -> String {
         if self.is_root {
             format!("Documentation for {}", builder.config.project.name)
@@ -326,4 +366,8 @@ impl<'e> OutputEntry<'e> for TutorialFolder {
                 .unwrap_or(format!("Tutorials for {}", builder.config.project.name))
         }
     }
+
+    fn cache_key(&self, _builder: &'e Builder<'e>) -> Option<u64> {
+        Some(hash_of(&self.index))
+    }
 }
@@ -0,0 +1,135 @@
+use crate::{builder::cache::CACHE_FILE, versions::VERSIONS_FILE};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Files that Flash always leaves in the output directory, used to make sure
+/// we're not about to delete something that isn't generated docs
+const MARKERS: [&str; 4] = ["nav.json", CACHE_FILE, "_analyze.cpp", VERSIONS_FILE];
+
+/// List of the entries Flash created in the output directory, so cleaning
+/// leaves everything else (like the `.git` of a gh-pages checkout) alone
+const MANIFEST_FILE: &str = ".flash-output.json";
+
+#[derive(Default, Serialize, Deserialize)]
+struct OutputManifest {
+    /// Names of the files and directories directly in the output directory
+    entries: BTreeSet<String>,
+}
+
+impl OutputManifest {
+    fn load(output_dir: &Path) -> Option<Self> {
+        serde_json::from_str(&fs::read_to_string(output_dir.join(MANIFEST_FILE)).ok()?).ok()
+    }
+}
+
+fn list_entries(dir: &Path) -> BTreeSet<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name != MANIFEST_FILE)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// What was in the output directory before a build, for recording which
+/// entries the build added
+pub struct OutputRecord {
+    output_dir: PathBuf,
+    before: BTreeSet<String>,
+}
+
+impl OutputRecord {
+    pub fn start(output_dir: &Path) -> Self {
+        Self {
+            output_dir: output_dir.to_path_buf(),
+            before: list_entries(output_dir),
+        }
+    }
+
+    /// Add the entries created since `start` to the ones earlier builds
+    /// created
+    pub fn finish(self) -> Result<(), String> {
+        let mut manifest = OutputManifest::load(&self.output_dir).unwrap_or_default();
+        manifest.entries.extend(
+            list_entries(&self.output_dir)
+                .into_iter()
+                .filter(|name| !self.before.contains(name)),
+        );
+        fs::write(
+            self.output_dir.join(MANIFEST_FILE),
+            serde_json::to_string(&manifest)
+                .map_err(|e| format!("Unable to save output manifest {e}"))?,
+        )
+        .map_err(|e| format!("Unable to save output manifest {e}"))
+    }
+}
+
+/// Remove the generated docs, including the sources Flash writes for parsing
+/// headers and examples. Only what Flash created is removed unless `force`
+/// is set, in which case the whole directory is
+pub fn clean_output(output_dir: &Path, force: bool) -> Result<(), String> {
+    if !output_dir.exists() {
+        info!("Nothing to clean in {}", output_dir.to_string_lossy());
+        return Ok(());
+    }
+    let manifest = OutputManifest::load(output_dir);
+    if manifest.is_none() && !MARKERS.iter().any(|m| output_dir.join(m).exists()) {
+        return Err(format!(
+            "{} does not look like a Flash output directory, refusing to remove it",
+            output_dir.to_string_lossy()
+        ));
+    }
+
+    if force {
+        fs::remove_dir_all(output_dir)
+            .map_err(|e| format!("Unable to remove {}: {e}", output_dir.to_string_lossy()))?;
+        info!("Removed {}", output_dir.to_string_lossy());
+        return Ok(());
+    }
+
+    let Some(manifest) = manifest else {
+        return Err(format!(
+            "{} has no record of which files Flash created, pass --force to remove the \
+            whole directory",
+            output_dir.to_string_lossy()
+        ));
+    };
+    for name in manifest
+        .entries
+        .iter()
+        .map(String::as_str)
+        .chain([MANIFEST_FILE])
+    {
+        let path = output_dir.join(name);
+        let res = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else if path.exists() {
+            fs::remove_file(&path)
+        } else {
+            Ok(())
+        };
+        res.map_err(|e| format!("Unable to remove {}: {e}", path.to_string_lossy()))?;
+    }
+
+    let kept = list_entries(output_dir);
+    if kept.is_empty() {
+        fs::remove_dir(output_dir)
+            .map_err(|e| format!("Unable to remove {}: {e}", output_dir.to_string_lossy()))?;
+        info!("Removed {}", output_dir.to_string_lossy());
+    } else {
+        info!(
+            "Removed the generated docs from {}, kept {} entries Flash didn't create",
+            output_dir.to_string_lossy(),
+            kept.len()
+        );
+    }
+    Ok(())
+}
@@ -1,5 +1,10 @@
 use serde::Deserialize;
-use std::{fs, path::PathBuf, process::Command, sync::Arc};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
 
 use crate::config::Config;
 
@@ -95,15 +100,10 @@ pub fn cmake_build(build_dir: &str, args: &Vec<String>) -> Result<(), String> {
         .ok_or("CMake build failed".into())
 }
 
-pub fn cmake_compile_commands(config: Arc<Config>) -> Result<CompileCommands, String> {
+pub fn cmake_compile_commands(build_dir: &Path) -> Result<CompileCommands, String> {
     serde_json::from_str(
-        &fs::read_to_string(
-            config
-                .input_dir
-                .join(&config.cmake.as_ref().unwrap().build_dir)
-                .join("compile_commands.json"),
-        )
-        .map_err(|e| format!("Unable to read compile_commands.json: {e}"))?,
+        &fs::read_to_string(build_dir.join("compile_commands.json"))
+            .map_err(|e| format!("Unable to read compile_commands.json: {e}"))?,
     )
     .map_err(|e| format!("Unable to parse compile_commands.json: {e}"))
 }
@@ -114,7 +114,10 @@ pub fn cmake_compile_args_for(config: Arc<Config>) -> Result<Vec<String>, String
         .as_ref()
         .ok_or(String::from("Project does not use CMake"))?
         .infer_args_from;
-    for cmd in cmake_compile_commands(config.clone())? {
+    let build_dir = config
+        .input_dir
+        .join(&config.cmake.as_ref().unwrap().build_dir);
+    for cmd in cmake_compile_commands(&build_dir)? {
         if cmd.file == config.input_dir.join(from) {
             return Ok(cmd.get_command_list(config));
         }
@@ -2,9 +2,17 @@ use flash_macros::decl_config;
 use glob::glob;
 use regex_lite::Regex;
 use serde::{Deserialize, Deserializer};
-use std::{fs, path::PathBuf, sync::Arc};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use crate::url::UrlPath;
+use crate::{
+    analyze::OutputFormat,
+    html::template::{Template, TemplateEngine},
+    url::UrlPath,
+};
 
 fn parse_template<'de, D>(deserializer: D) -> Result<Arc<String>, D::Error>
 where
@@ -16,6 +24,15 @@ where
     ))
 }
 
+fn parse_page_template<'de, D>(deserializer: D) -> Result<Arc<Template>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Arc::from(
+        Template::load(&PathBuf::deserialize(deserializer)?).map_err(serde::de::Error::custom)?,
+    ))
+}
+
 fn parse_sources<'de, D>(deserializer: D) -> Result<Vec<Arc<Source>>, D::Error>
 where
     D: Deserializer<'de>,
@@ -67,6 +84,15 @@ macro_rules! default_template {
     };
 }
 
+macro_rules! default_page_template {
+    ($name: expr) => {
+        Arc::from(
+            Template::new(include_str!($name).to_string(), TemplateEngine::Strfmt)
+                .expect("Default templates are valid"),
+        )
+    };
+}
+
 macro_rules! default_scripts {
     () => {
         Vec::new(),
@@ -164,6 +190,9 @@ decl_config! {
         analysis {
             compile_args: Vec<String> = Vec::new(),
         },
+        output {
+            format: OutputFormat = OutputFormat::Html,
+        },
         cmake? {
             config_args: Vec<String> = Vec::new(),
             build_args: Vec<String> = Vec::new(),
@@ -172,15 +201,23 @@ decl_config! {
             infer_args_from: PathBuf,
         },
         templates {
-            class:          Arc<String> as parse_template = default_template!("../templates/class.html"),
-            struct_:        Arc<String> as parse_template = default_template!("../templates/struct.html"),
-            function:       Arc<String> as parse_template = default_template!("../templates/function.html"),
-            head:           Arc<String> as parse_template = default_template!("../templates/head.html"),
-            nav:            Arc<String> as parse_template = default_template!("../templates/nav.html"),
-            file:           Arc<String> as parse_template = default_template!("../templates/file.html"),
-            page:           Arc<String> as parse_template = default_template!("../templates/page.html"),
-            tutorial:       Arc<String> as parse_template = default_template!("../templates/tutorial.html"),
-            tutorial_index: Arc<String> as parse_template = default_template!("../templates/tutorial-index.html"),
+            class:          Arc<Template> as parse_page_template = default_page_template!("../templates/class.html"),
+            struct_:        Arc<Template> as parse_page_template = default_page_template!("../templates/struct.html"),
+            function:       Arc<Template> as parse_page_template = default_page_template!("../templates/function.html"),
+            enum_:          Arc<Template> as parse_page_template = default_page_template!("../templates/enum.html"),
+            alias:          Arc<Template> as parse_page_template = default_page_template!("../templates/alias.html"),
+            variable:       Arc<Template> as parse_page_template = default_page_template!("../templates/variable.html"),
+            macro_:         Arc<Template> as parse_page_template = default_page_template!("../templates/macro.html"),
+            namespace:      Arc<Template> as parse_page_template = default_page_template!("../templates/namespace.html"),
+            changelog:      Arc<Template> as parse_page_template = default_page_template!("../templates/changelog.html"),
+            hierarchy:      Arc<Template> as parse_page_template = default_page_template!("../templates/hierarchy.html"),
+            includes:       Arc<Template> as parse_page_template = default_page_template!("../templates/includes.html"),
+            head:           Arc<Template> as parse_page_template = default_page_template!("../templates/head.html"),
+            nav:            Arc<Template> as parse_page_template = default_page_template!("../templates/nav.html"),
+            file:           Arc<Template> as parse_page_template = default_page_template!("../templates/file.html"),
+            page:           Arc<Template> as parse_page_template = default_page_template!("../templates/page.html"),
+            tutorial:       Arc<Template> as parse_page_template = default_page_template!("../templates/tutorial.html"),
+            tutorial_index: Arc<Template> as parse_page_template = default_page_template!("../templates/tutorial-index.html"),
         },
         scripts {
             css: Vec<Script> = default_scripts!("default.css", "nav.css", "content.css", "themes.css"),
@@ -194,6 +231,7 @@ decl_config! {
         let input_dir: PathBuf,
         let output_dir: PathBuf,
         let output_url: Option<UrlPath>,
+        let versions_url: Option<UrlPath>,
     }
 }
 
@@ -203,18 +241,77 @@ impl Config {
         output_dir: PathBuf,
         output_url: Option<UrlPath>,
     ) -> Result<Arc<Config>, String> {
-        let mut config: Config = toml::from_str(
-            &fs::read_to_string(input_dir.join("flash.toml"))
-                .map_err(|e| format!("Unable to read flash.toml: {e}"))?,
-        )
-        .map_err(|e| format!("Unable to parse config: {e}"))?;
-
+        let mut config = Self::read(&input_dir)?;
         config.input_dir = input_dir;
         config.output_dir = output_dir;
         config.output_url = output_url;
         Ok(Arc::from(config))
     }
 
+    /// Parse the config for building the docs into a subdirectory of the
+    /// output directory named after the project version, so multiple
+    /// versions can be hosted side by side
+    pub fn parse_versioned(
+        input_dir: PathBuf,
+        root_dir: PathBuf,
+        root_url: Option<UrlPath>,
+    ) -> Result<Arc<Config>, String> {
+        let mut config = Self::read(&input_dir)?;
+        let version = config.project.version.clone();
+        if version.is_empty()
+            || version == "."
+            || version == ".."
+            || version == crate::versions::LATEST_ALIAS
+            || version.contains(['/', '\\'])
+        {
+            return Err(format!(
+                "Project version \"{version}\" can't be used as a directory name"
+            ));
+        }
+
+        let root_url = root_url.unwrap_or_else(UrlPath::new);
+        config.input_dir = input_dir;
+        config.output_dir = root_dir.join(&version);
+        config.output_url = Some(root_url.join(UrlPath::part(&version)));
+        config.versions_url = Some(root_url);
+        Ok(Arc::from(config))
+    }
+
+    fn read(input_dir: &Path) -> Result<Config, String> {
+        Self::from_toml(
+            &fs::read_to_string(input_dir.join("flash.toml"))
+                .map_err(|e| format!("Unable to read flash.toml: {e}"))?,
+        )
+    }
+
+    /// Parse the contents of a flash.toml
+    pub fn from_toml(data: &str) -> Result<Config, String> {
+        toml::from_str(data).map_err(|e| format!("Unable to parse config: {e}"))
+    }
+
+    pub fn all_templates(&self) -> Vec<&Arc<Template>> {
+        let t = &self.templates;
+        vec![
+            &t.class,
+            &t.struct_,
+            &t.function,
+            &t.enum_,
+            &t.alias,
+            &t.variable,
+            &t.macro_,
+            &t.namespace,
+            &t.changelog,
+            &t.hierarchy,
+            &t.includes,
+            &t.head,
+            &t.nav,
+            &t.file,
+            &t.page,
+            &t.tutorial,
+            &t.tutorial_index,
+        ]
+    }
+
     pub fn all_includes(&self) -> Vec<PathBuf> {
         self.sources
             .iter()
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 pub mod process;
+pub mod template;
 
 pub trait GenHtml: Into<Html> {
     fn gen_html(self) -> String;
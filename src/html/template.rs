@@ -0,0 +1,93 @@
+use minijinja::{AutoEscape, Environment, Value};
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    path::Path,
+};
+use strfmt::strfmt;
+
+/// Which engine a template is filled with
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum TemplateEngine {
+    /// `{name}` keys are replaced with pre-rendered HTML
+    Strfmt,
+    /// Jinja syntax with conditionals and loops. Pages also get structured
+    /// data about what they document
+    Jinja,
+}
+
+#[derive(Debug)]
+pub struct Template {
+    source: String,
+    engine: TemplateEngine,
+    /// Jinja templates are parsed once here instead of for every page
+    env: Option<Environment<'static>>,
+}
+
+impl Hash for Template {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+        self.engine.hash(state);
+    }
+}
+
+impl Template {
+    pub fn new(source: String, engine: TemplateEngine) -> Result<Self, String> {
+        let env = match engine {
+            TemplateEngine::Strfmt => None,
+            TemplateEngine::Jinja => {
+                let mut env = Environment::new();
+                env.set_auto_escape_callback(|_| AutoEscape::Html);
+                env.add_template_owned("page", source.clone())
+                    .map_err(|e| e.to_string())?;
+                Some(env)
+            }
+        };
+        Ok(Self {
+            source,
+            engine,
+            env,
+        })
+    }
+
+    /// Load a template from a file. Files ending in `.jinja` or `.j2` use the
+    /// Jinja engine and everything else uses strfmt
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read template {}: {e}", path.to_string_lossy()))?;
+        let engine = match path.extension().and_then(|e| e.to_str()) {
+            Some("jinja" | "j2") => TemplateEngine::Jinja,
+            _ => TemplateEngine::Strfmt,
+        };
+        Self::new(source, engine)
+            .map_err(|e| format!("Unable to parse template {}: {e}", path.to_string_lossy()))
+    }
+
+    /// Fill in the template. The variables are HTML and are inserted as-is,
+    /// while strings in the data are escaped
+    pub fn render(
+        &self,
+        vars: &HashMap<String, String>,
+        data: Option<&serde_json::Value>,
+    ) -> Result<String, String> {
+        match &self.env {
+            None => strfmt(&self.source, vars).map_err(|e| e.to_string()),
+            Some(env) => {
+                let mut context = HashMap::new();
+                if let Some(serde_json::Value::Object(data)) = data {
+                    context.extend(
+                        data.iter()
+                            .map(|(k, v)| (k.as_str(), Value::from_serialize(v))),
+                    );
+                }
+                context.extend(
+                    vars.iter()
+                        .map(|(k, v)| (k.as_str(), Value::from_safe_string(v.clone()))),
+                );
+                env.get_template("page")
+                    .and_then(|t| t.render(context))
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+}
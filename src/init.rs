@@ -0,0 +1,329 @@
+use crate::{
+    cmake::{CompileCommand, cmake_compile_commands, cmake_configure},
+    config::Config,
+    normalize::Normalize,
+};
+use log::{info, warn};
+use regex_lite::Regex;
+use std::{
+    cmp::Reverse,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const HEADER_EXTENSIONS: [&str; 4] = ["hpp", "h", "hh", "hxx"];
+const SOURCE_EXTENSIONS: [&str; 4] = ["cpp", "cc", "cxx", "c"];
+
+/// Separate build dir so configuring for docs doesn't mess with the user's
+/// own build
+const BUILD_DIR: &str = "build-docs";
+
+/// Needed for CMake to write compile_commands.json
+const CONFIG_ARGS: &str = "-DCMAKE_EXPORT_COMPILE_COMMANDS=ON";
+
+struct GuessedSource {
+    name: String,
+    dir: PathBuf,
+    patterns: Vec<String>,
+}
+
+/// Recursively find all files in a directory, skipping hidden directories
+/// and anything that looks like a build directory
+fn find_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.') && !name.starts_with("build") && name != "_deps" {
+                find_files(&path, out);
+            }
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+fn has_extension(path: &Path, exts: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| exts.contains(&e))
+}
+
+/// Get the project name and version from the `project()` call in
+/// CMakeLists.txt, falling back to the directory name
+fn guess_project(input_dir: &Path) -> (String, String) {
+    let dir_name = input_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or("project".into());
+
+    let Ok(cmake) = fs::read_to_string(input_dir.join("CMakeLists.txt")) else {
+        return (dir_name, "1.0.0".into());
+    };
+    let Some(caps) = Regex::new(r"(?i)project\s*\(\s*([\w-]+)([^)]*)\)")
+        .unwrap()
+        .captures(&cmake)
+    else {
+        return (dir_name, "1.0.0".into());
+    };
+    let version = Regex::new(r"VERSION\s+([\d.]+)")
+        .unwrap()
+        .captures(caps.get(2).unwrap().as_str())
+        .map(|v| v[1].to_owned())
+        .unwrap_or("1.0.0".into());
+    (caps[1].to_owned(), version)
+}
+
+/// Glob patterns matching all the kinds of headers in a directory
+fn header_patterns(dir: &Path, files: &[PathBuf]) -> Vec<String> {
+    HEADER_EXTENSIONS
+        .iter()
+        .filter(|ext| {
+            files
+                .iter()
+                .filter(|f| f.starts_with(dir))
+                .any(|f| has_extension(f, &[ext]))
+        })
+        .map(|ext| format!("**/*.{ext}"))
+        .collect()
+}
+
+/// Get the include directories passed to the compiler with `-I`
+fn include_dirs(cmd: &CompileCommand) -> Vec<PathBuf> {
+    let args = shlex::split(&cmd.command).unwrap_or_default();
+    let mut dirs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let dir = if arg == "-I" {
+            iter.next().cloned()
+        } else {
+            arg.strip_prefix("-I").map(|d| d.to_owned())
+        };
+        if let Some(dir) = dir {
+            dirs.push(cmd.directory.join(dir).normalize());
+        }
+    }
+    dirs
+}
+
+/// Guess which directories contain the public headers based on the include
+/// directories of the project's own translation units
+fn guess_sources_from_commands(
+    input_dir: &Path,
+    name: &str,
+    commands: &[CompileCommand],
+    files: &[PathBuf],
+) -> Vec<GuessedSource> {
+    let build_dir = input_dir.join(BUILD_DIR);
+    let mut dirs = commands
+        .iter()
+        .filter(|cmd| cmd.file.starts_with(input_dir) && !cmd.file.starts_with(&build_dir))
+        .flat_map(include_dirs)
+        .filter(|dir| dir.starts_with(input_dir) && !dir.starts_with(&build_dir))
+        .filter(|dir| !header_patterns(dir, files).is_empty())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.dedup();
+
+    // Headers in nested include dirs are already covered by the outer one
+    let outer = dirs
+        .iter()
+        .filter(|dir| {
+            !dirs
+                .iter()
+                .any(|other| *dir != other && dir.starts_with(other))
+        })
+        .collect::<Vec<_>>();
+
+    outer
+        .iter()
+        .map(|dir| GuessedSource {
+            name: if outer.len() == 1 {
+                name.to_owned()
+            } else {
+                dir.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or(name.to_owned())
+            },
+            dir: dir.to_path_buf(),
+            patterns: header_patterns(dir, files),
+        })
+        .collect()
+}
+
+/// Use the project's `include` directory if there is one, otherwise
+/// everything in the project
+fn guess_sources_from_files(input_dir: &Path, name: &str, files: &[PathBuf]) -> Vec<GuessedSource> {
+    let dir = if input_dir.join("include").is_dir() {
+        input_dir.join("include")
+    } else {
+        input_dir.to_path_buf()
+    };
+    let patterns = header_patterns(&dir, files);
+    if patterns.is_empty() {
+        return Vec::new();
+    }
+    vec![GuessedSource {
+        name: name.to_owned(),
+        dir,
+        patterns,
+    }]
+}
+
+/// Pick the translation unit that includes the most of the documented
+/// headers, since its compile args are the most likely to be able to parse
+/// all of them
+fn guess_translation_unit(
+    input_dir: &Path,
+    commands: &[CompileCommand],
+    sources: &[GuessedSource],
+) -> Option<PathBuf> {
+    let build_dir = input_dir.join(BUILD_DIR);
+    let include_regex = Regex::new(r#"^\s*#\s*include\s*[<"]([^>"]+)[>"]"#).unwrap();
+    commands
+        .iter()
+        .filter(|cmd| cmd.file.starts_with(input_dir) && !cmd.file.starts_with(&build_dir))
+        .map(|cmd| {
+            let score = fs::read_to_string(&cmd.file)
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| include_regex.captures(line))
+                .filter(|caps| sources.iter().any(|src| src.dir.join(&caps[1]).is_file()))
+                .count();
+            (cmd, score)
+        })
+        .max_by_key(|(cmd, score)| (*score, Reverse(cmd.file.as_os_str().len())))
+        .map(|(cmd, _)| cmd.file.clone())
+}
+
+fn quote_list(list: &[String]) -> String {
+    list.iter()
+        .map(|s| format!("\"{s}\""))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn relative_str(path: &Path, to: &Path) -> String {
+    let rel = path
+        .strip_prefix(to)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    if rel.is_empty() { ".".into() } else { rel }
+}
+
+/// Configure the CMake project and read the compile commands from it
+fn configure(input_dir: &Path) -> Result<Vec<CompileCommand>, String> {
+    info!("Configuring CMake project into {BUILD_DIR}");
+    cmake_configure(BUILD_DIR, &vec![CONFIG_ARGS.to_owned()])?;
+    cmake_compile_commands(&input_dir.join(BUILD_DIR))
+}
+
+/// Write a `flash.toml` for the project in the input directory based on
+/// what's in it. The input directory must be the working directory
+pub fn init_config(input_dir: &Path, overwrite: bool) -> Result<(), String> {
+    let config_path = input_dir.join("flash.toml");
+    if config_path.exists() && !overwrite {
+        return Err(format!(
+            "{} already exists and no --overwrite option was specified",
+            config_path.to_string_lossy()
+        ));
+    }
+
+    let mut files = Vec::new();
+    find_files(input_dir, &mut files);
+
+    let (name, version) = guess_project(input_dir);
+
+    let commands = if input_dir.join("CMakeLists.txt").exists() {
+        match configure(input_dir) {
+            Ok(commands) => Some(commands),
+            Err(e) => {
+                warn!("{e}, guessing the config from the files in the project instead");
+                Some(Vec::new())
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut sources = commands
+        .as_ref()
+        .map(|cmds| guess_sources_from_commands(input_dir, &name, cmds, &files))
+        .unwrap_or_default();
+    if sources.is_empty() {
+        sources = guess_sources_from_files(input_dir, &name, &files);
+    }
+    if sources.is_empty() {
+        return Err(format!(
+            "No headers found in {}",
+            input_dir.to_string_lossy()
+        ));
+    }
+
+    let mut data = format!(
+        "# Generated by `flash init`, see the README for all the options\n\
+        \n\
+        [project]\n\
+        name = \"{name}\"\n\
+        version = \"{version}\"\n\
+        # repository = \"https://github.com/user/{name}\"\n"
+    );
+
+    for src in &sources {
+        data += &format!(
+            "\n\
+            # Headers to generate docs for, relative to `dir`\n\
+            [[sources]]\n\
+            name = \"{}\"\n\
+            dir = \"{}\"\n\
+            include = [{}]\n",
+            src.name,
+            relative_str(&src.dir, input_dir),
+            quote_list(&src.patterns)
+        );
+    }
+
+    if let Some(commands) = commands {
+        let unit = guess_translation_unit(input_dir, &commands, &sources).or_else(|| {
+            // Any source file will do for getting the include paths and defines
+            files
+                .iter()
+                .find(|f| has_extension(f, &SOURCE_EXTENSIONS))
+                .cloned()
+        });
+        if unit.is_none() {
+            warn!("No source files found, set cmake.infer-args-from in flash.toml manually");
+        }
+        data += &format!(
+            "\n\
+            [cmake]\n\
+            # Where to configure the project for getting compile args\n\
+            build-dir = \"{BUILD_DIR}\"\n\
+            # Flash needs compile_commands.json to get the compile args\n\
+            config-args = [\"{CONFIG_ARGS}\"]\n\
+            # The source file whose compile args are used for parsing the headers\n\
+            infer-args-from = \"{}\"\n",
+            unit.map(|u| relative_str(&u, input_dir))
+                .unwrap_or("src/main.cpp".into())
+        );
+    }
+
+    // Make sure what we're about to write actually works
+    Config::from_toml(&data).map_err(|e| format!("Generated flash.toml is invalid: {e}"))?;
+
+    fs::write(&config_path, data).map_err(|e| format!("Unable to write flash.toml: {e}"))?;
+
+    info!("Created {}", config_path.to_string_lossy());
+    for src in &sources {
+        info!(
+            "Documenting headers in {}",
+            relative_str(&src.dir, input_dir)
+        );
+    }
+
+    Ok(())
+}
@@ -2,109 +2,419 @@
 #![feature(iter_advance_by)]
 #![feature(iter_intersperse)]
 
-use crate::{analyze::create_docs, normalize::Normalize, url::UrlPath};
-use clap::Parser;
+use crate::{
+    analyze::{BuildOptions, OutputFormat, create_docs},
+    builder::{diff::ApiDiff, export::ApiExport},
+    clean::OutputRecord,
+    normalize::Normalize,
+    url::UrlPath,
+};
+use clap::{Parser, Subcommand};
 use config::Config;
 use log::{error, info};
-use std::{error::Error, fs, path::PathBuf, process::exit, time::Instant};
+use std::{fs, path::PathBuf, process::ExitCode, time::Instant};
 
 mod analyze;
 mod annotation;
 mod builder;
+mod clean;
 mod cmake;
 mod config;
 mod html;
+mod init;
 mod lookahead;
 mod normalize;
+mod serve;
 mod url;
+mod versions;
 
 #[derive(Parser, Debug)]
 #[command(name("Flash"), version, about)]
 struct Args {
-    /// Input directory with the flash.json file
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Args, Debug)]
+struct InputArgs {
+    /// Input directory with the flash.toml file
     #[arg(short, long)]
     input: PathBuf,
 
+    /// Whether to skip invoking CMake entirely, relies on existing build dir.
+    #[arg(long, default_value_t = false, hide = true)]
+    skip_build: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct OutputArgs {
     /// Output directory where to place the generated docs
     #[arg(short, long)]
     output: PathBuf,
+}
 
-    /// Whether to overwrite output directory if it already exists
-    #[arg(long, default_value_t = false)]
-    overwrite: bool,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Build the docs
+    Build {
+        #[command(flatten)]
+        input: InputArgs,
 
-    /// Whether to skip invoking CMake entirely, relies on existing build dir.
-    #[arg(long, default_value_t = false, hide = true)]
-    skip_build: bool,
-}
+        #[command(flatten)]
+        output: OutputArgs,
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    FlashLogger::init();
+        /// Whether to overwrite output directory if it already exists
+        #[arg(long, default_value_t = false)]
+        overwrite: bool,
 
-    let args = Args::parse();
+        /// What to generate. Defaults to the format in flash.toml, or HTML
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Fail the build if less than this percentage of the public API is documented
+        #[arg(long, value_name = "PERCENT")]
+        coverage_fail_under: Option<f64>,
+
+        /// Treat problems in doc comments as errors
+        #[arg(long, default_value_t = false)]
+        strict: bool,
+
+        /// Generate a changelog page against the api.json of a previous version
+        #[arg(long, value_name = "API_JSON")]
+        changes_since: Option<PathBuf>,
+
+        /// Build into a subdirectory named after the project version and
+        /// link it with the other versions in the output directory
+        #[arg(long, default_value_t = false)]
+        versioned: bool,
+    },
+
+    /// Check the doc comments, links and coverage without keeping any pages
+    Check {
+        #[command(flatten)]
+        input: InputArgs,
+
+        /// Fail if less than this percentage of the public API is documented
+        #[arg(long, value_name = "PERCENT")]
+        coverage_fail_under: Option<f64>,
+    },
+
+    /// Build the docs, serve them locally and rebuild them on changes
+    Serve {
+        #[command(flatten)]
+        input: InputArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+
+        /// Port to serve the docs on
+        #[arg(long, default_value_t = 8000)]
+        port: u16,
+    },
+
+    /// Compare two versions of the documented API
+    Diff {
+        /// The api.json or project directory of the old version
+        old: PathBuf,
+
+        /// The api.json or project directory of the new version
+        new: PathBuf,
+
+        /// Where to write the changes as markdown, printed if not specified
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Fail if there are any breaking changes
+        #[arg(long, default_value_t = false)]
+        fail_on_breaking: bool,
+
+        /// Whether to skip invoking CMake entirely, relies on existing build dir.
+        #[arg(long, default_value_t = false, hide = true)]
+        skip_build: bool,
+    },
+
+    /// Create a flash.toml for an existing project
+    Init {
+        /// Directory of the project to create the config for
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Whether to overwrite flash.toml if it already exists
+        #[arg(long, default_value_t = false)]
+        overwrite: bool,
+    },
+
+    /// Remove generated docs
+    Clean {
+        #[command(flatten)]
+        output: OutputArgs,
+
+        /// Remove the whole output directory, including files Flash didn't
+        /// create
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+}
 
+/// Get the absolute version of a path relative to the working directory.
+/// Not using fs::canonicalize because that returns UNC paths on Windows and
+/// those break things
+fn absolute(path: PathBuf) -> Result<PathBuf, String> {
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Ok(std::env::current_dir()
+            .map_err(|e| format!("Unable to get working directory: {e}"))?
+            .join(path)
+            .normalize())
+    }
+}
+
+/// Create the output directory and get its absolute path and the URL the
+/// docs will be hosted at. Must be called before `enter_input_dir`
+fn prepare_output(output: PathBuf, overwrite: bool) -> Result<(PathBuf, Option<UrlPath>), String> {
     // Check if output dir exists
-    if args.output.exists()
+    if output.exists()
         // Check if it's empty
-        && args.output.read_dir().map(|mut i| i.next().is_some()).unwrap_or(false)
+        && output.read_dir().map(|mut i| i.next().is_some()).unwrap_or(false)
         // Then overwrite must be specified
-        && !args.overwrite
+        && !overwrite
     {
-        error!(
+        return Err(format!(
             "Output directory {} already exists and no --overwrite option was specified, aborting",
-            args.output.to_string_lossy()
-        );
-        exit(1);
+            output.to_string_lossy()
+        ));
     }
 
-    if !args.output.exists() {
-        fs::create_dir_all(&args.output)?;
+    if !output.exists() {
+        fs::create_dir_all(&output)
+            .map_err(|e| format!("Unable to create output directory: {e}"))?;
     }
 
-    let relative_output = if args.output.is_relative() {
-        Some(UrlPath::try_from(&args.output).ok()).flatten()
+    let relative_output = if output.is_relative() {
+        Some(UrlPath::try_from(&output).ok()).flatten()
     } else {
         None
     };
+    Ok((absolute(output)?, relative_output))
+}
 
-    // Relink working directory to input dir and use absolute path for output
-    // Not using fs::canonicalize because that returns UNC paths on Windows and
-    // those break things
-    let full_output = if args.output.is_absolute() {
-        args.output
-    } else {
-        std::env::current_dir()?.join(args.output).normalize()
-    };
-    let full_input = if args.input.is_absolute() {
-        args.input
-    } else {
-        std::env::current_dir()?.join(args.input).normalize()
-    };
-    std::env::set_current_dir(&full_input).expect(
-        "Unable to set input dir as working directory \
-            (probable reason is it doesn't exist)",
-    );
-
-    // Parse config
-    let conf = Config::parse(full_input, full_output, relative_output)?;
-
-    // Build the docs
-    info!(
-        "Building docs for {} ({})",
-        conf.project.name, conf.project.version
-    );
-    let now = Instant::now();
-    create_docs(conf.clone(), args.skip_build).await?;
-    info!(
-        "Docs built for {} in {}s",
-        conf.project.name,
-        now.elapsed().as_secs()
-    );
+/// Relink working directory to input dir, since all paths in the config are
+/// relative to it
+fn enter_input_dir(input: PathBuf) -> Result<PathBuf, String> {
+    let full_input = absolute(input)?;
+    std::env::set_current_dir(&full_input).map_err(|e| {
+        format!(
+            "Unable to set input dir {} as working directory: {e}",
+            full_input.to_string_lossy()
+        )
+    })?;
+    Ok(full_input)
+}
+
+/// Temporary directory for the files Flash writes while parsing when it
+/// isn't building docs
+fn scratch_dir(name: &str) -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir().join(format!("flash-{name}-{}", std::process::id()));
+    fs::create_dir_all(&dir).map_err(|e| format!("Unable to create temporary directory: {e}"))?;
+    Ok(dir)
+}
+
+/// Load an API export, or analyze a project to create one. The path must be
+/// absolute
+async fn load_api(path: PathBuf, skip_build: bool) -> Result<ApiExport, String> {
+    if !path.is_dir() {
+        return ApiExport::load(&path);
+    }
+
+    let scratch = scratch_dir("diff")?;
+    let conf = Config::parse(enter_input_dir(path)?, scratch.clone(), None)?;
+    info!("Analyzing {} ({})", conf.project.name, conf.project.version);
+    let res = create_docs(
+        conf,
+        BuildOptions {
+            format: OutputFormat::Json,
+            skip_build,
+            ..Default::default()
+        },
+    )
+    .await
+    .and_then(|_| ApiExport::load(&scratch.join("api.json")));
+    fs::remove_dir_all(&scratch).ok();
+    res
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    match args.command {
+        Command::Build {
+            input,
+            output,
+            overwrite,
+            format,
+            coverage_fail_under,
+            strict,
+            changes_since,
+            versioned,
+        } => {
+            let changes_since = changes_since.map(absolute).transpose()?;
+            // Other versions are expected to be in the output directory
+            let (full_output, relative_output) =
+                prepare_output(output.output, overwrite || versioned)?;
+            let full_input = enter_input_dir(input.input)?;
+            let record = OutputRecord::start(&full_output);
 
+            // Parse config
+            let conf = if versioned {
+                let conf = Config::parse_versioned(full_input, full_output, relative_output)?;
+                // Only rebuilding the same version needs --overwrite. The
+                // path is absolute so the working directory doesn't matter
+                prepare_output(conf.output_dir.clone(), overwrite)?;
+                conf
+            } else {
+                Config::parse(full_input, full_output, relative_output)?
+            };
+            let format = format.unwrap_or(conf.output.format);
+            if versioned && format != OutputFormat::Html {
+                return Err("Only the HTML docs can be built with --versioned".into());
+            }
+
+            // Build the docs
+            info!(
+                "Building docs for {} ({})",
+                conf.project.name, conf.project.version
+            );
+            let now = Instant::now();
+            let res = create_docs(
+                conf.clone(),
+                BuildOptions {
+                    format,
+                    skip_build: input.skip_build,
+                    coverage_fail_under,
+                    strict,
+                    check_only: false,
+                    changes_since,
+                },
+            )
+            .await
+            .and_then(|_| {
+                if versioned {
+                    versions::update_versions(&conf)?;
+                }
+                Ok(())
+            });
+            // Record what was written even if the build failed partway
+            record.finish()?;
+            res?;
+            info!(
+                "Docs built for {} in {}s",
+                conf.project.name,
+                now.elapsed().as_secs()
+            );
+        }
+
+        Command::Check {
+            input,
+            coverage_fail_under,
+        } => {
+            let scratch = scratch_dir("check")?;
+            let full_input = enter_input_dir(input.input)?;
+
+            let conf = Config::parse(full_input, scratch.clone(), None)?;
+            info!(
+                "Checking docs for {} ({})",
+                conf.project.name, conf.project.version
+            );
+            let res = create_docs(
+                conf.clone(),
+                BuildOptions {
+                    skip_build: input.skip_build,
+                    coverage_fail_under,
+                    strict: true,
+                    check_only: true,
+                    ..Default::default()
+                },
+            )
+            .await;
+            fs::remove_dir_all(&scratch).ok();
+            res?;
+            info!("No problems found in the docs for {}", conf.project.name);
+        }
+
+        Command::Serve {
+            input,
+            output,
+            port,
+        } => {
+            // Serving always rebuilds into the output dir
+            let (full_output, relative_output) = prepare_output(output.output, true)?;
+            let full_input = enter_input_dir(input.input)?;
+            serve::serve(
+                full_input,
+                full_output,
+                relative_output,
+                port,
+                input.skip_build,
+            )
+            .await?;
+        }
+
+        Command::Diff {
+            old,
+            new,
+            output,
+            fail_on_breaking,
+            skip_build,
+        } => {
+            let new = absolute(new)?;
+            let output = output.map(absolute).transpose()?;
+            let old_api = load_api(absolute(old)?, skip_build).await?;
+            let new_api = load_api(new, skip_build).await?;
+
+            let diff = ApiDiff::new(&old_api, &new_api);
+            match output {
+                Some(output) => fs::write(&output, diff.to_markdown())
+                    .map_err(|e| format!("Unable to write {}: {e}", output.to_string_lossy()))?,
+                None => print!("{}", diff.to_markdown()),
+            }
+            info!(
+                "{} to {}: {}",
+                diff.old_version,
+                diff.new_version,
+                diff.summary()
+            );
+            if fail_on_breaking && diff.breaking().next().is_some() {
+                return Err("Found breaking changes".into());
+            }
+        }
+
+        Command::Init { input, overwrite } => {
+            let full_input = enter_input_dir(input)?;
+            init::init_config(&full_input, overwrite)?;
+        }
+
+        Command::Clean { output, force } => {
+            clean::clean_output(&absolute(output.output)?, force)?;
+        }
+    }
     Ok(())
 }
 
+#[tokio::main]
+async fn main() -> ExitCode {
+    FlashLogger::init();
+
+    // Invalid arguments exit with code 2
+    let args = Args::parse();
+
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
 struct FlashLogger;
 
 impl log::Log for FlashLogger {
@@ -121,7 +431,9 @@ impl log::Log for FlashLogger {
             Level::Info => "[info]".bright_blue().bold().to_string(),
             Level::Debug | Level::Trace => "[debug]".bright_purple().bold().to_string(),
         };
-        println!("{} {}", header, record.args());
+        // Logs go to stderr so commands that print their result, like
+        // `diff`, can be piped to a file
+        eprintln!("{} {}", header, record.args());
     }
 
     fn flush(&self) {}
@@ -0,0 +1,367 @@
+use crate::{
+    analyze::{BuildOptions, create_docs},
+    clean::OutputRecord,
+    config::Config,
+    url::UrlPath,
+};
+use log::{error, info};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::watch,
+};
+
+/// Endpoint the injected script listens on for reload events
+const RELOAD_ENDPOINT: &str = "/__flash_reload";
+
+/// How often to check the watched files for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn reload_script() -> String {
+    format!(
+        "<script>new EventSource('{RELOAD_ENDPOINT}').onmessage = () => location.reload();</script>"
+    )
+}
+
+fn collect_files(path: &Path, ignore: &[PathBuf], files: &mut HashMap<PathBuf, SystemTime>) {
+    if ignore.iter().any(|i| path.starts_with(i)) {
+        return;
+    }
+    if path.is_dir() {
+        if let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.flatten() {
+                collect_files(&entry.path(), ignore, files);
+            }
+        }
+    } else if let Ok(time) = fs::metadata(path).and_then(|m| m.modified()) {
+        files.insert(path.to_path_buf(), time);
+    }
+}
+
+/// Get the paths of custom templates and scripts specified in flash.toml.
+/// These are read when the config is parsed so the parsed config doesn't
+/// know where they came from
+fn config_template_paths(input_dir: &Path) -> Vec<PathBuf> {
+    let Some(value) = fs::read_to_string(input_dir.join("flash.toml"))
+        .ok()
+        .and_then(|s| s.parse::<toml::Value>().ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut paths = Vec::new();
+    if let Some(templates) = value.get("templates").and_then(|t| t.as_table()) {
+        paths.extend(
+            templates
+                .values()
+                .filter_map(|v| v.as_str())
+                .map(PathBuf::from),
+        );
+    }
+    if let Some(scripts) = value.get("scripts").and_then(|t| t.as_table()) {
+        paths.extend(
+            scripts
+                .values()
+                .filter_map(|v| v.as_array())
+                .flatten()
+                .filter_map(|s| s.get("content").and_then(|c| c.as_str()))
+                .map(PathBuf::from),
+        );
+    }
+    paths
+}
+
+/// Snapshot the modification times of everything that affects the output
+fn watched_files(
+    input_dir: &Path,
+    output_dir: &Path,
+    config: Option<&Config>,
+) -> HashMap<PathBuf, SystemTime> {
+    // Don't watch files generated by the build itself, or every build would
+    // trigger another one if the sources are at the project root
+    let mut ignore = vec![output_dir.to_path_buf()];
+    if let Some(cmake) = config.and_then(|c| c.cmake.as_ref()) {
+        ignore.push(input_dir.join(&cmake.build_dir));
+    }
+
+    let mut files = HashMap::new();
+    collect_files(&input_dir.join("flash.toml"), &ignore, &mut files);
+    for path in config_template_paths(input_dir) {
+        collect_files(&input_dir.join(path), &ignore, &mut files);
+    }
+    if let Some(config) = config {
+        for src in &config.sources {
+            collect_files(&input_dir.join(src.dir.to_pathbuf()), &ignore, &mut files);
+        }
+        if let Some(ref tutorials) = config.tutorials {
+            collect_files(&input_dir.join(&tutorials.dir), &ignore, &mut files);
+        }
+    }
+    files
+}
+
+async fn rebuild(
+    input_dir: &Path,
+    output_dir: &Path,
+    output_url: Option<UrlPath>,
+    skip_build: bool,
+) -> Result<Arc<Config>, String> {
+    let conf = Config::parse(
+        input_dir.to_path_buf(),
+        output_dir.to_path_buf(),
+        output_url,
+    )?;
+    info!(
+        "Building docs for {} ({})",
+        conf.project.name, conf.project.version
+    );
+    let now = Instant::now();
+    let record = OutputRecord::start(output_dir);
+    let res = create_docs(
+        conf.clone(),
+        BuildOptions {
+            skip_build,
+            ..Default::default()
+        },
+    )
+    .await;
+    record.finish()?;
+    res?;
+    info!(
+        "Docs built for {} in {}s",
+        conf.project.name,
+        now.elapsed().as_secs()
+    );
+    Ok(conf)
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        body.len()
+    );
+    for (key, value) in headers {
+        head += &format!("{key}: {value}\r\n");
+    }
+    head += "\r\n";
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await
+}
+
+/// Map a request path to a file in the output directory. Returns `Err` with
+/// the location to redirect to if the path is outside the served root
+fn resolve_path(output_dir: &Path, prefix: &str, path: &str) -> Result<Option<PathBuf>, String> {
+    let Some(rest) = path.strip_prefix(prefix) else {
+        return Err(format!("{prefix}/"));
+    };
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return Err(format!("{prefix}/"));
+    }
+
+    // Files are written with their url-safe names, so the path does not
+    // need to be decoded
+    let mut file = output_dir.to_path_buf();
+    for part in rest.split('/').filter(|p| !p.is_empty()) {
+        if part == ".." || part == "." {
+            return Ok(None);
+        }
+        file.push(part);
+    }
+    if file.is_dir() {
+        file.push("index.html");
+    }
+    Ok(file.is_file().then_some(file))
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    output_dir: Arc<PathBuf>,
+    prefix: Arc<String>,
+    mut reload: watch::Receiver<usize>,
+) -> std::io::Result<()> {
+    let mut buf = vec![0; 8192];
+    let len = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..len]);
+
+    let mut request_line = request.lines().next().unwrap_or("").split(' ');
+    let method = request_line.next().unwrap_or("");
+    let target = request_line.next().unwrap_or("/");
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", &[], b"").await;
+    }
+
+    if path == RELOAD_ENDPOINT {
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\n\
+                Content-Type: text/event-stream\r\n\
+                Cache-Control: no-cache\r\n\
+                Connection: keep-alive\r\n\r\n",
+            )
+            .await?;
+        // The receiver is cloned from one that never reads, so without this
+        // every connection after the first rebuild would reload right away
+        reload.borrow_and_update();
+        // The sender is only dropped when the server shuts down
+        while reload.changed().await.is_ok() {
+            stream.write_all(b"data: reload\n\n").await?;
+        }
+        return Ok(());
+    }
+
+    let file = match resolve_path(&output_dir, &prefix, path) {
+        Ok(file) => file,
+        Err(location) => {
+            return respond(&mut stream, "302 Found", &[("Location", &location)], b"").await;
+        }
+    };
+
+    let Some(file) = file else {
+        return respond(
+            &mut stream,
+            "404 Not Found",
+            &[("Content-Type", "text/plain; charset=utf-8")],
+            b"404 Not Found",
+        )
+        .await;
+    };
+
+    let Ok(mut body) = fs::read(&file) else {
+        return respond(&mut stream, "500 Internal Server Error", &[], b"").await;
+    };
+
+    let content_type = content_type(&file);
+    if content_type.starts_with("text/html") {
+        let html = String::from_utf8_lossy(&body);
+        body = match html.rfind("</body>") {
+            Some(pos) => format!("{}{}{}", &html[..pos], reload_script(), &html[pos..]),
+            None => format!("{html}{}", reload_script()),
+        }
+        .into_bytes();
+    }
+
+    let headers = [
+        ("Content-Type", content_type),
+        ("Cache-Control", "no-cache"),
+    ];
+    respond(&mut stream, "200 OK", &headers, &body).await
+}
+
+async fn run_server(
+    listener: TcpListener,
+    output_dir: PathBuf,
+    prefix: String,
+    reload: watch::Receiver<usize>,
+) {
+    let output_dir = Arc::new(output_dir);
+    let prefix = Arc::new(prefix);
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        tokio::spawn(handle_connection(
+            stream,
+            output_dir.clone(),
+            prefix.clone(),
+            reload.clone(),
+        ));
+    }
+}
+
+/// Build the docs, serve them on localhost and rebuild them whenever the
+/// config, headers, tutorials or templates change. Open pages are reloaded
+/// automatically after each successful rebuild
+pub async fn serve(
+    input_dir: PathBuf,
+    output_dir: PathBuf,
+    output_url: Option<UrlPath>,
+    port: u16,
+    skip_build: bool,
+) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Unable to listen on port {port}: {e}"))?;
+
+    let prefix = output_url
+        .as_ref()
+        .map(|url| url.to_string())
+        .unwrap_or_default();
+
+    let (reload_tx, reload_rx) = watch::channel(0usize);
+    tokio::spawn(run_server(
+        listener,
+        output_dir.clone(),
+        prefix.clone(),
+        reload_rx,
+    ));
+
+    let mut config = match rebuild(&input_dir, &output_dir, output_url.clone(), skip_build).await {
+        Ok(conf) => Some(conf),
+        Err(e) => {
+            error!("{e}");
+            None
+        }
+    };
+    let mut files = watched_files(&input_dir, &output_dir, config.as_deref());
+
+    info!("Serving docs at http://localhost:{port}{prefix}/");
+    info!("Watching for changes, press Ctrl+C to stop");
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let current = watched_files(&input_dir, &output_dir, config.as_deref());
+        if current == files {
+            continue;
+        }
+
+        info!("Changes detected, rebuilding");
+        // Only configure CMake again if no build has succeeded yet
+        match rebuild(
+            &input_dir,
+            &output_dir,
+            output_url.clone(),
+            skip_build || config.is_some(),
+        )
+        .await
+        {
+            Ok(conf) => {
+                config = Some(conf);
+                reload_tx.send_modify(|n| *n += 1);
+            }
+            Err(e) => error!("{e}"),
+        }
+        // Pick up any files added by the new config
+        files = watched_files(&input_dir, &output_dir, config.as_deref());
+    }
+}
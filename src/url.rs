@@ -39,21 +39,31 @@ pub const URL_RESERVED: &AsciiSet = &CONTROLS
 #[derive(Hash, Debug, Clone, PartialEq)]
 pub struct UrlPath {
     parts: Vec<String>,
+    /// Fragment (the `#part`) of the URL, if any
+    fragment: Option<String>,
 }
 
 #[allow(unused)]
 impl UrlPath {
     pub fn new() -> Self {
-        Self { parts: Vec::new() }
+        Self {
+            parts: Vec::new(),
+            fragment: None,
+        }
     }
 
     pub fn new_with_path(parts: Vec<String>) -> Self {
-        Self { parts }.clean()
+        Self {
+            parts,
+            fragment: None,
+        }
+        .clean()
     }
 
     pub fn part(part: &str) -> Self {
         Self {
             parts: vec![part.to_string()],
+            fragment: None,
         }
     }
 
@@ -86,16 +96,31 @@ impl UrlPath {
     pub fn join<T: AsRef<UrlPath>>(&self, other: T) -> Self {
         let mut buf = self.clone();
         buf.parts.extend(other.as_ref().parts.clone());
+        // The fragment always belongs to the last part of the path
+        buf.fragment = other.as_ref().fragment.clone();
         buf.clean()
     }
 
+    /// Create a copy of this URL pointing to the given fragment (`#fragment`)
+    pub fn with_fragment<T: AsRef<str>>(&self, fragment: T) -> Self {
+        let mut copy = self.clone();
+        copy.fragment = Some(fragment.as_ref().to_owned());
+        copy
+    }
+
+    pub fn fragment(&self) -> Option<&String> {
+        self.fragment.as_ref()
+    }
+
     /// Strip prefix. If prefix is not a prefix of this URL, nothing happens
     pub fn strip_prefix<T: AsRef<UrlPath>>(&self, prefix: T) -> Self {
         // Make sure prefix is shorter or as long as path
         if self.parts.len() >= prefix.as_ref().parts.len()
             && self.parts[0..prefix.as_ref().parts.len()] == prefix.as_ref().parts
         {
-            return UrlPath::new_with_path(self.parts[prefix.as_ref().parts.len()..].into());
+            let mut res = UrlPath::new_with_path(self.parts[prefix.as_ref().parts.len()..].into());
+            res.fragment = self.fragment.clone();
+            return res;
         }
         self.clone()
     }
@@ -168,7 +193,14 @@ impl UrlPath {
         } else {
             "/"
         };
-        format!("{leading}{}", &self.parts.join("/"))
+        format!(
+            "{leading}{}{}",
+            &self.parts.join("/"),
+            self.fragment
+                .as_ref()
+                .map(|f| format!("#{f}"))
+                .unwrap_or_default()
+        )
     }
 }
 
@@ -258,9 +290,14 @@ impl Display for UrlPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // empty urls are just ""
         if !self.is_empty() {
-            f.write_fmt(format_args!("/{}", &self.url_safe_parts().join("/")))
-        } else {
-            Ok(())
+            f.write_fmt(format_args!("/{}", &self.url_safe_parts().join("/")))?;
+        }
+        if let Some(ref fragment) = self.fragment {
+            f.write_fmt(format_args!(
+                "#{}",
+                utf8_percent_encode(fragment, URL_RESERVED)
+            ))?;
         }
+        Ok(())
     }
 }
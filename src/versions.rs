@@ -0,0 +1,187 @@
+use crate::config::Config;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Manifest listing all the built versions, kept in the root output directory
+pub const VERSIONS_FILE: &str = "versions.json";
+
+/// List of the pages in a version, used for finding the same page in other
+/// versions
+pub const PAGES_FILE: &str = "pages.json";
+
+/// Directory redirecting to the newest version
+pub const LATEST_ALIAS: &str = "latest";
+
+/// Marks redirect pages written by Flash so they can be safely replaced
+const REDIRECT_MARKER: &str = "<!-- flash-redirect -->";
+
+#[derive(Serialize, Deserialize)]
+struct VersionEntry {
+    version: String,
+    url: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct VersionsManifest {
+    latest: Option<String>,
+    /// Newest first
+    versions: Vec<VersionEntry>,
+}
+
+/// Key for ordering versions like `1.10.0` after `1.9.2`, and prereleases
+/// like `2.0.0-beta` before the release itself
+fn version_key(version: &str) -> (Vec<u64>, bool, String) {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+    (
+        core.split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect(),
+        pre.is_none(),
+        pre.unwrap_or_default().to_owned(),
+    )
+}
+
+/// Find the directories of all the pages in a built version, relative to it
+pub fn find_pages(dir: &Path, rel: &str, out: &mut Vec<String>) {
+    if dir.join("index.html").is_file() {
+        out.push(rel.to_owned());
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.path().is_dir() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rel = if rel.is_empty() {
+                name
+            } else {
+                format!("{rel}/{name}")
+            };
+            find_pages(&entry.path(), &rel, out);
+        }
+    }
+}
+
+fn write_redirect(dir: &Path, target: &str) -> Result<(), String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Unable to create {}: {e}", dir.to_string_lossy()))?;
+    fs::write(
+        dir.join("index.html"),
+        format!(
+            "<!DOCTYPE html>\n{REDIRECT_MARKER}\n\
+            <html>\n\
+            <head>\n\
+            <meta charset=\"UTF-8\">\n\
+            <meta http-equiv=\"refresh\" content=\"0; url={target}\">\n\
+            <link rel=\"canonical\" href=\"{target}\">\n\
+            </head>\n\
+            <body><a href=\"{target}\">Redirecting to {target}</a></body>\n\
+            </html>\n"
+        ),
+    )
+    .map_err(|e| format!("Unable to write redirect to {target}: {e}"))
+}
+
+/// Replace the `latest` alias with redirects to every page of the version
+/// hosted at `version_url`
+fn update_latest_alias(root_dir: &Path, version_url: &str, pages: &[String]) -> Result<(), String> {
+    let latest_dir = root_dir.join(LATEST_ALIAS);
+    if latest_dir.exists() {
+        fs::remove_dir_all(&latest_dir)
+            .map_err(|e| format!("Unable to remove old {LATEST_ALIAS} alias: {e}"))?;
+    }
+    for page in pages {
+        let mut dir = latest_dir.clone();
+        dir.extend(page.split('/').filter(|p| !p.is_empty()));
+        let target = if page.is_empty() {
+            format!("{version_url}/")
+        } else {
+            format!("{version_url}/{page}/")
+        };
+        write_redirect(&dir, &target)?;
+    }
+    Ok(())
+}
+
+/// Record the version that was just built in the manifest, list its pages
+/// and point the `latest` alias to the newest version. The output directory
+/// of the config must be the version's directory
+pub fn update_versions(config: &Config) -> Result<(), String> {
+    let (Some(root_url), Some(version_url)) = (&config.versions_url, &config.output_url) else {
+        return Err("Docs are not being built with --versioned".into());
+    };
+    let root_dir: PathBuf = config
+        .output_dir
+        .parent()
+        .ok_or("Versioned output directory has no parent")?
+        .to_path_buf();
+    let version = &config.project.version;
+
+    let mut pages = Vec::new();
+    find_pages(&config.output_dir, "", &mut pages);
+    pages.sort();
+    fs::write(
+        config.output_dir.join(PAGES_FILE),
+        serde_json::to_string(&pages).map_err(|e| format!("Unable to save page list {e}"))?,
+    )
+    .map_err(|e| format!("Unable to save page list {e}"))?;
+
+    let manifest_path = root_dir.join(VERSIONS_FILE);
+    let mut manifest = match fs::read_to_string(&manifest_path) {
+        Ok(data) => serde_json::from_str::<VersionsManifest>(&data)
+            .map_err(|e| format!("Unable to parse {VERSIONS_FILE}: {e}"))?,
+        Err(_) => VersionsManifest::default(),
+    };
+
+    // Versions whose directory was removed by hand shouldn't be linked to
+    manifest
+        .versions
+        .retain(|v| v.version != *version && root_dir.join(&v.version).is_dir());
+    manifest.versions.push(VersionEntry {
+        version: version.clone(),
+        url: version_url.to_string(),
+    });
+    manifest
+        .versions
+        .sort_by_key(|v| std::cmp::Reverse(version_key(&v.version)));
+
+    // The alias is rebuilt from the newest version's page list every time so
+    // it also gets fixed if that version's pages changed
+    let newest = &manifest.versions[0];
+    let newest_pages = if newest.version == *version {
+        pages
+    } else {
+        fs::read_to_string(root_dir.join(&newest.version).join(PAGES_FILE))
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_else(|| vec![String::new()])
+    };
+    update_latest_alias(&root_dir, &newest.url, &newest_pages)?;
+    info!("Pointed {LATEST_ALIAS} to {}", newest.version);
+    manifest.latest = Some(newest.version.clone());
+
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Unable to save {VERSIONS_FILE} {e}"))?,
+    )
+    .map_err(|e| format!("Unable to save {VERSIONS_FILE} {e}"))?;
+
+    // Send visitors of the root to the newest docs, unless the user has put
+    // their own page there
+    let custom_index = fs::read_to_string(root_dir.join("index.html"))
+        .is_ok_and(|html| !html.contains(REDIRECT_MARKER));
+    if !custom_index {
+        write_redirect(&root_dir, &format!("{root_url}/{LATEST_ALIAS}/"))?;
+    }
+
+    Ok(())
+}
@@ -12,7 +12,25 @@ const searchX = document.getElementById('nav-clear-x');
 let searchNav = undefined;
 let searchQuery = '';
 
-let memberFunctionsList = null;
+let searchIndex = null;
+let searchIndexLoading = false;
+
+// How important each kind of search result is, and which icon to show for it
+const SEARCH_KINDS = {
+    namespace:  { weight: 6, icon: ['folder', false] },
+    class:      { weight: 8, icon: ['box', false] },
+    struct:     { weight: 8, icon: ['box', true] },
+    function:   { weight: 7, icon: ['code', true] },
+    method:     { weight: 5, icon: ['code', false] },
+    enum:       { weight: 6, icon: ['list', false] },
+    alias:      { weight: 5, icon: ['link', false] },
+    variable:   { weight: 4, icon: ['hash', false] },
+    field:      { weight: 3, icon: ['hash', false] },
+    macro:      { weight: 4, icon: ['terminal', false] },
+    enumerator: { weight: 2, icon: ['list', false] },
+    tutorial:   { weight: 7, icon: ['bookmark', false] },
+    section:    { weight: 4, icon: ['bookmark', false] },
+};
 
 function createCopyButton(icon, text, callback = undefined) {
     const button = document.createElement('button');
@@ -152,12 +170,22 @@ function debounce(func, delay) {
 
 function searchActually(query) {
     searchQuery = query;
-    if (!memberFunctionsList && selectedNavTab() == 'entities') {
-        fetch(`${FLASH_OUTPUT_URL}/functions.json`)
+    if (!searchIndex && !searchIndexLoading && query.length) {
+        searchIndexLoading = true;
+        fetch(`${FLASH_OUTPUT_URL}/search.json`)
         .then(res => res.json())
-        .then(res => {
-            memberFunctionsList = res;
+        .then(({ shards }) => Promise.all(
+            [...Array(shards).keys()].map(i =>
+                fetch(`${FLASH_OUTPUT_URL}/search-${i}.json`).then(res => res.json())
+            )
+        ))
+        .then(shards => {
+            searchIndex = shards.flat();
             searchActually(searchQuery);
+        })
+        .catch(err => {
+            console.error(err);
+            searchIndexLoading = false;
         });
     }
     updateNav();
@@ -177,6 +205,15 @@ function getFullName(node) {
     return result;
 }
 
+// matches are shown with innerHTML so names like `Foo<Bar>` have to be escaped
+function escapeHTML(str) {
+    return str
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;');
+}
+
 function furryMatch(str, query) {
     // remove all whitespace from query since entities can't have that anyway
     // todo: maybe split query to words instead and only require some of those to match instead of whole query
@@ -215,14 +252,14 @@ function furryMatch(str, query) {
             if (matchedInARow === 1) {
                 matchedString += '<span class="matched">';
             }
-            matchedString += current;
+            matchedString += escapeHTML(current);
 
             // match next char in query next
             toMatch++;
             // if at end, stop matching
             if (toMatch === query.length) {
                 matchedString += '</span>';
-                matchedString += str.substring(i + 1);
+                matchedString += escapeHTML(str.substring(i + 1));
                 break;
             }
         }
@@ -231,7 +268,7 @@ function furryMatch(str, query) {
             if (matchedInARow) {
                 matchedString += '</span>';
             }
-            matchedString += current;
+            matchedString += escapeHTML(current);
             matchedInARow = 0;
         }
     }
@@ -271,7 +308,7 @@ function furryMatchMany(list, query, separator) {
             }
         }
         else {
-            matched += item;
+            matched += escapeHTML(item);
         }
         i++;
     }
@@ -282,6 +319,62 @@ function furryMatchMany(list, query, separator) {
     return someMatched ? { score, matched } : undefined;
 }
 
+function matchSearchEntry(entry, query) {
+    const kind = SEARCH_KINDS[entry.kind] ?? { weight: 0 };
+
+    // Matching the name is best
+    const match = (entry.kind === 'tutorial' || entry.kind === 'section') ?
+        furryMatch(entry.name, query) :
+        furryMatchMany(entry.full_name.split('::'), query, '::');
+    if (match) {
+        return { score: match.score + kind.weight * 2, matched: match.matched };
+    }
+
+    // Then all the words of the query being words in the name
+    const words = query.toLowerCase().split(/[\s:]+/).filter(w => w.length);
+    if (!words.length) {
+        return undefined;
+    }
+    if (words.every(w => entry.tokens.some(t => t.startsWith(w)))) {
+        return { score: kind.weight + words.length, matched: escapeHTML(entry.full_name) };
+    }
+
+    // And finally the query appearing in the docs
+    const text = `${entry.summary ?? ''} ${entry.text ?? ''}`.toLowerCase();
+    if (words.every(w => text.includes(w))) {
+        return { score: kind.weight - 10, matched: escapeHTML(entry.full_name) };
+    }
+    return undefined;
+}
+
+function searchIndexResults() {
+    const tutorials = selectedNavTab() == 'tutorials';
+    const results = [];
+    for (const entry of searchIndex) {
+        const isTutorial = entry.kind === 'tutorial' || entry.kind === 'section';
+        if (isTutorial !== tutorials) {
+            continue;
+        }
+        const match = matchSearchEntry(entry, searchQuery);
+        if (match) {
+            const node = document.createElement('a');
+            node.setAttribute('href', entry.url);
+            if (entry.summary) {
+                node.setAttribute('title', entry.summary);
+            }
+            node.addEventListener('click', e => {
+                navigate(entry.url);
+                e.preventDefault();
+            });
+            const [icon, variant] = SEARCH_KINDS[entry.kind]?.icon ?? ['hash', false];
+            node.innerHTML = feather.icons[icon].toSvg({ 'class': `icon${variant ? ' variant' : ''}` }) +
+                match.matched;
+            results.push([match.score, node]);
+        }
+    }
+    return results;
+}
+
 function currentNav() {
     return nav.querySelector(`#nav-content-${selectedNavTab()}`);
 }
@@ -310,39 +403,23 @@ function updateNav() {
             searchResults.classList.add('monospace');
         }
 
-        const results = [];
-        currentNav().querySelectorAll('a').forEach(a => {
-            const match = furryMatchMany(
-                getFullName(a), searchQuery,
-                selectedNavTab() == 'entities' ? '::' : '/'
-            );
-            if (match) {
-                const clone = a.cloneNode(false);
-                const svg = a.querySelector('svg');
-                clone.innerHTML = match.matched;
-                // copy any icons over
-                if (svg) {
-                    clone.insertBefore(svg.cloneNode(true), clone.firstChild);
-                }
-                results.push([match.score, clone]);
-            }
-        });
-        if (selectedNavTab() == 'entities') {
-            memberFunctionsList?.forEach(fun => {
-                let funParts = fun.split('::');
-                const name = funParts.at(-1);
-                const match = furryMatchMany(funParts, searchQuery, '::');
+        // Use the search index once it's loaded, and the navigation until then
+        const results = searchIndex ? searchIndexResults() : [];
+        if (!searchIndex) {
+            currentNav().querySelectorAll('a').forEach(a => {
+                const match = furryMatchMany(
+                    getFullName(a), searchQuery,
+                    selectedNavTab() == 'entities' ? '::' : '/'
+                );
                 if (match) {
-                    funParts.pop();
-                    const node = document.createElement('a');
-                    const url = `${FLASH_OUTPUT_URL}/classes/${funParts.join('/')}#${name.replace(/\s+\([0-9]+\)/, '')}`;
-                    node.setAttribute('href', url);
-                    node.addEventListener('click', e => {
-                        navigate(url);
-                        e.preventDefault();
-                    });
-                    node.innerHTML = feather.icons.code.toSvg({ 'class': 'icon class' }) + match.matched;
-                    results.push([match.score, node]);
+                    const clone = a.cloneNode(false);
+                    const svg = a.querySelector('svg');
+                    clone.innerHTML = match.matched;
+                    // copy any icons over
+                    if (svg) {
+                        clone.insertBefore(svg.cloneNode(true), clone.firstChild);
+                    }
+                    results.push([match.score, clone]);
                 }
             });
         }
@@ -389,7 +466,10 @@ function scrollAndOpenElement(id) {
         if (id.startsWith('#')) {
             id = id.substring(1);
         }
-        const target = document.getElementById(id);
+        // overloaded functions have anchors like `name-int-float`, so
+        // `#name` should still go to the first overload
+        const target = document.getElementById(id) ??
+            document.querySelector(`[id^="${CSS.escape(id)}-"]`);
         if (target) {
             target.scrollIntoView();
             document.querySelectorAll('.highlight')
@@ -495,6 +575,7 @@ function navigate(url) {
                 ...metadata,
             }, "", url);
             document.title = metadata.title;
+            updateVersions();
             mainBody.innerHTML = content;
             mainBody.scrollTo({ left: 0, top: 0 });
             nav.querySelectorAll('a.selected').forEach(a => a.classList.remove('selected'));
@@ -516,10 +597,66 @@ window.onpopstate = e => {
     if (e.state) {
         mainBody.innerHTML = e.state.html;
         document.title = e.state.title;
+        updateVersions();
         highlight();
     }
 };
 
+// Contents of versions.json and the pages each version has, only loaded if
+// the docs were built with --versioned
+let versions = null;
+const versionPages = {};
+
+// Path of the current page relative to the root of this version
+function currentPagePath() {
+    let path = window.location.pathname;
+    if (path.startsWith(FLASH_OUTPUT_URL)) {
+        path = path.slice(FLASH_OUTPUT_URL.length);
+    }
+    return path.replace(/^\/+|\/+$/g, '');
+}
+
+async function buildVersions() {
+    if (typeof FLASH_VERSIONS_URL === 'undefined' || FLASH_VERSIONS_URL === null) return;
+    try {
+        versions = await fetch(`${FLASH_VERSIONS_URL}/versions.json`).then(res => res.json());
+        await Promise.all(versions.versions.map(async v => {
+            const pages = await fetch(`${v.url}/pages.json`)
+                .then(res => res.json())
+                .catch(_ => []);
+            versionPages[v.version] = new Set(pages);
+        }));
+    } catch (e) {
+        console.error(e);
+        versions = null;
+        return;
+    }
+    document.getElementById('nav-versions').style.display = '';
+    updateVersions();
+}
+
+// Point each version in the selector to the current page in that version, or
+// to its front page if the page doesn't exist there
+function updateVersions() {
+    if (!versions) return;
+    const page = currentPagePath();
+    const options = versions.versions.map(v => {
+        const option = document.createElement('option');
+        const exists = versionPages[v.version]?.has(page) ?? false;
+        option.value = exists && page ? `${v.url}/${page}/${window.location.hash}` : `${v.url}/`;
+        option.textContent = v.version;
+        if (v.version === versions.latest) option.textContent += ' (latest)';
+        if (!exists) option.textContent += ' (page missing)';
+        option.selected = v.url === FLASH_OUTPUT_URL;
+        return option;
+    });
+    document.getElementById('nav-version-select').replaceChildren(...options);
+}
+
+function switchVersion(url) {
+    window.location.href = url;
+}
+
 document.querySelectorAll('[data-pick-theme]').forEach(btn => {
     btn.addEventListener('click', e => {
         pickTheme(btn.getAttribute('data-pick-theme'));
@@ -547,6 +684,7 @@ function toggleMenu() {
 }
 
 await buildNav();
+buildVersions();
 
 // Highlight everything
 try {
@@ -596,4 +734,5 @@ document.querySelector(`[data-pick-theme="${
 // expose these to the html
 window.showNav = showNav;
 window.clearSearch = clearSearch;
-window.toggleMenu = toggleMenu;
\ No newline at end of file
+window.toggleMenu = toggleMenu;
+window.switchVersion = switchVersion;
\ No newline at end of file